@@ -0,0 +1,61 @@
+use aoc_2022::day6::{find_marker, find_marker_bitmask, repeat_in_four, repeat_in_window};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const STREAM_LEN: usize = 4 * 1024 * 1024;
+
+// Build a stream with no marker of `size` until the very end: random letters drawn from only
+// `size - 1` distinct letters, followed by `size` distinct letters
+fn stream_for(size: usize) -> Vec<u8> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut stream: Vec<u8> = (0..STREAM_LEN)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            b'a' + (state % (size as u64 - 1)) as u8
+        })
+        .collect();
+    stream.extend((0..size as u8).map(|x| b'a' + x));
+    stream
+}
+
+// How the solvers search, with one of their original repeat checks
+fn take_while(stream: &[u8], size: usize, has_repeat: fn(&[u8]) -> bool) -> usize {
+    stream.windows(size).take_while(|x| has_repeat(x)).count() + size
+}
+
+fn bench_markers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day6 marker");
+    group.sample_size(20);
+    for size in [4, 14, 26] {
+        let stream = stream_for(size);
+        // The part 1 check is hardcoded to four bytes, so it only gets compared at that size
+        if size == 4 {
+            group.bench_with_input(BenchmarkId::new("part1 closure", size), &stream, |b, s| {
+                b.iter(|| take_while(black_box(s), size, repeat_in_four))
+            });
+        }
+        group.bench_with_input(BenchmarkId::new("part2 closure", size), &stream, |b, s| {
+            b.iter(|| take_while(black_box(s), size, repeat_in_window))
+        });
+        group.bench_with_input(BenchmarkId::new("find_marker", size), &stream, |b, s| {
+            b.iter(|| find_marker(black_box(s), size))
+        });
+        group.bench_with_input(BenchmarkId::new("bitmask", size), &stream, |b, s| {
+            b.iter(|| find_marker_bitmask(black_box(s), size))
+        });
+
+        // Same thing, but with the trailing newline a real input file has
+        let mut with_newline = stream.clone();
+        with_newline.push(b'\n');
+        group.bench_with_input(
+            BenchmarkId::new("bitmask with newline", size),
+            &with_newline,
+            |b, s| b.iter(|| find_marker_bitmask(black_box(s), size)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_markers);
+criterion_main!(benches);
@@ -1,50 +1,447 @@
 // Day 1: Calorie Counting - https://adventofcode.com/2022/day/1
 //
-// Generator: Parses the input using iterators into a Vec<u32>
+// Generator: Parses the input line by line into a Vec<Elf>, where each `Elf` keeps track of its
+// position in the input and the calories of every item it carries. Lines are trimmed, so Windows
+// line endings are fine, and any run of blank lines separates two elves. A line that isn't a
+// number stops parsing with a `ParseCaloriesError` pointing at that line, rather than being
+// counted as zero calories.
 //
-// Part 1: Sorts the Vec<u32> using sort_unstable (which is faster, preserving the original
-// order does not matter for this situation), then returns the last (largest) element
+// Part 1: Asks for the single elf carrying the most calories using `top_n`, and returns its total
+// (or nothing, if the input didn't have any elves)
 //
-// Part 2: Sorts the Vec<u32> using sort_unstable, then splits off the last three elements,
-// using `reduce` on the resulting iterator to sum the elements together
+// Part 2: Asks for the top three elves using `top_n`, and sums their totals together
 //
+// Top N: Rather than sorting every elf, we keep a min-heap of the best `n` elves seen so far. Each
+// new elf gets pushed, and if the heap grows past `n`, the smallest one gets popped off again, so
+// the heap never holds more than `n + 1` elves. Ties go to the elf that came first in the input.
+//
+// Report: `CalorieStats` sorts every elf's total once, which makes the median and percentiles
+// simple lookups (interpolating between the two closest totals). The histogram splits the range
+// between the smallest and largest total into equally sized buckets, and draws a bar for each one
+// scaled against the fullest bucket. The `Report` solver prints all of it from the CLI.
+//
+// Redistribution: To get every elf under a capacity while moving as few items as possible, the
+// heuristic first has each overloaded elf keep as many items as it can, which means keeping its
+// smallest items. Everything else goes into a pool that gets handed out largest first, each item
+// going to the elf with the least room left that can still take it (best fit decreasing). This is
+// quick, but can give up on inputs that do have a plan. The exact planner tries every elf for every
+// item with a depth-first search, trying to leave items where they are first and cutting off any
+// branch that has already moved as many items as the best plan so far. It is exponential, so it's
+// only meant for small inputs.
+//
+use std::{cmp::Reverse, collections::BinaryHeap, error::Error, fmt, num::ParseIntError};
 
-#[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<u32> {
-    let split: Vec<u32> = input
-        .split("\n\n")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elf {
+    pub index: usize,
+    pub items: Vec<u32>,
+}
+
+impl Elf {
+    // Summed as u64, since enough u32 items can overflow a u32 total
+    pub fn total(&self) -> u64 {
+        self.items.iter().copied().map(u64::from).sum()
+    }
+}
+
+// Get the `n` elves carrying the most calories, most first
+pub fn top_n(elves: &[Elf], n: usize) -> Vec<&Elf> {
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+    for (position, elf) in elves.iter().enumerate() {
+        heap.push(Reverse((elf.total(), Reverse(position))));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
         .into_iter()
-        .map(|x| {
-            x.split('\n')
-                .into_iter()
-                .map(|x| x.parse::<u32>().unwrap_or_default())
-                .reduce(|a, b| a + b)
-                .unwrap()
-        })
+        .map(|Reverse((_, Reverse(position)))| &elves[position])
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalorieStats {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    // Every elf's total, smallest first
+    totals: Vec<u64>,
+}
+
+impl CalorieStats {
+    // Get the statistics for a group of elves, or `None` if there are no elves at all
+    pub fn from_elves(elves: &[Elf]) -> Option<Self> {
+        let mut totals: Vec<u64> = elves.iter().map(Elf::total).collect();
+        if totals.is_empty() {
+            return None;
+        }
+        totals.sort_unstable();
+
+        let count = totals.len();
+        let mean = totals.iter().map(|x| *x as f64).sum::<f64>() / count as f64;
+        let variance = totals
+            .iter()
+            .map(|x| (*x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+
+        let mut stats = CalorieStats {
+            count,
+            mean,
+            median: 0.0,
+            std_dev: variance.sqrt(),
+            totals,
+        };
+        stats.median = stats.percentile(50.0);
+        Some(stats)
+    }
+
+    pub fn min(&self) -> u64 {
+        self.totals[0]
+    }
+
+    pub fn max(&self) -> u64 {
+        self.totals[self.count - 1]
+    }
+
+    // Get the total below which `p` percent of elves fall, interpolating between neighbours
+    pub fn percentile(&self, p: f64) -> f64 {
+        let rank = p.clamp(0.0, 100.0) / 100.0 * (self.count - 1) as f64;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+        let (low, high) = (self.totals[below] as f64, self.totals[above] as f64);
+        low + (high - low) * (rank - below as f64)
+    }
+
+    // Draw a histogram with `buckets` rows, the longest bar being `width` characters
+    pub fn histogram(&self, buckets: usize, width: usize) -> String {
+        let buckets = buckets.max(1) as u64;
+        let bucket_size = (self.max() - self.min()) / buckets + 1;
+
+        let mut counts = vec![0; buckets as usize];
+        for total in &self.totals {
+            counts[((total - self.min()) / bucket_size) as usize] += 1;
+        }
+        let fullest = counts.iter().copied().max().unwrap_or(1);
+
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let lower = self.min() + i as u64 * bucket_size;
+                let bar = "#".repeat(count * width / fullest);
+                format!(
+                    "{lower:>8}-{:<8} | {bar} {count}\n",
+                    lower + bucket_size - 1
+                )
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for CalorieStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "elves:   {}", self.count)?;
+        writeln!(f, "mean:    {:.1}", self.mean)?;
+        writeln!(f, "median:  {:.1}", self.median)?;
+        writeln!(f, "std dev: {:.1}", self.std_dev)?;
+        writeln!(f, "min:     {}", self.min())?;
+        writeln!(f, "max:     {}", self.max())?;
+        for p in [25.0, 75.0, 90.0, 99.0] {
+            writeln!(f, "p{p:<7}{:.1}", self.percentile(p))?;
+        }
+        write!(f, "{}", self.histogram(10, 40))
+    }
+}
+
+// A single item being handed from one elf to another, using the elves' indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub calories: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+// Quickly plan which items to move so that no elf carries more than `capacity` calories. Gives
+// `None` if it can't find a plan, even though one might exist
+pub fn plan_redistribution(elves: &[Elf], capacity: u64) -> Option<Vec<Move>> {
+    let mut loads = Vec::with_capacity(elves.len());
+    let mut pool = Vec::new();
+
+    // Each elf keeps its smallest items, for as long as they fit
+    for (position, elf) in elves.iter().enumerate() {
+        let mut items = elf.items.clone();
+        items.sort_unstable();
+        let mut load = 0;
+        for calories in items {
+            if load + u64::from(calories) <= capacity {
+                load += u64::from(calories);
+            } else {
+                pool.push((calories, position));
+            }
+        }
+        loads.push(load);
+    }
+
+    pool.sort_unstable_by(|a, b| b.cmp(a));
+    let mut moves = Vec::with_capacity(pool.len());
+    for (calories, from) in pool {
+        let to = (0..elves.len())
+            .filter(|x| loads[*x] + u64::from(calories) <= capacity)
+            .max_by_key(|x| loads[*x])?;
+        loads[to] += u64::from(calories);
+        moves.push(Move {
+            calories,
+            from: elves[from].index,
+            to: elves[to].index,
+        });
+    }
+    Some(moves)
+}
+
+struct RedistributionSearch {
+    // Every item as (calories, position of the elf carrying it), largest first
+    items: Vec<(u32, usize)>,
+    capacity: u64,
+    loads: Vec<u64>,
+    assignment: Vec<usize>,
+    best: Option<(usize, Vec<usize>)>,
+}
+
+impl RedistributionSearch {
+    fn search(&mut self, moved: usize) {
+        if matches!(&self.best, Some((best, _)) if moved >= *best) {
+            return;
+        }
+        let next = self.assignment.len();
+        let Some(&(calories, owner)) = self.items.get(next) else {
+            self.best = Some((moved, self.assignment.clone()));
+            return;
+        };
+
+        let targets = std::iter::once(owner).chain((0..self.loads.len()).filter(|x| *x != owner));
+        for target in targets {
+            if self.loads[target] + u64::from(calories) > self.capacity {
+                continue;
+            }
+            self.loads[target] += u64::from(calories);
+            self.assignment.push(target);
+            self.search(moved + usize::from(target != owner));
+            self.assignment.pop();
+            self.loads[target] -= u64::from(calories);
+        }
+    }
+}
+
+// Plan the fewest possible item moves so that no elf carries more than `capacity` calories, or
+// `None` if there is no way to do it. Exponential in the number of items
+pub fn plan_redistribution_exact(elves: &[Elf], capacity: u64) -> Option<Vec<Move>> {
+    let mut items: Vec<(u32, usize)> = elves
+        .iter()
+        .enumerate()
+        .flat_map(|(position, elf)| elf.items.iter().map(move |x| (*x, position)))
         .collect();
+    items.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut search = RedistributionSearch {
+        assignment: Vec::with_capacity(items.len()),
+        items,
+        capacity,
+        loads: vec![0; elves.len()],
+        best: None,
+    };
+    search.search(0);
+
+    let (_, assignment) = search.best?;
+    Some(
+        search
+            .items
+            .iter()
+            .zip(assignment)
+            .filter(|((_, owner), to)| owner != to)
+            .map(|((calories, from), to)| Move {
+                calories: *calories,
+                from: elves[*from].index,
+                to: elves[to].index,
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseCaloriesError {
+    // 1-based line number of the offending line
+    pub line: usize,
+    pub content: String,
+    pub source: ParseIntError,
+}
 
-    split
+impl fmt::Display for ParseCaloriesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: invalid calorie count {:?} ({})",
+            self.line, self.content, self.source
+        )
+    }
+}
+
+impl Error for ParseCaloriesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[aoc_generator(day1)]
+pub fn input_generator(input: &str) -> Result<Vec<Elf>, ParseCaloriesError> {
+    let mut elves = Vec::new();
+    let mut items = Vec::new();
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        // A blank line finishes off the current elf, if we've started one
+        if line.is_empty() {
+            if !items.is_empty() {
+                elves.push(Elf {
+                    index: elves.len(),
+                    items: std::mem::take(&mut items),
+                });
+            }
+            continue;
+        }
+        let calories = line.parse().map_err(|source| ParseCaloriesError {
+            line: number + 1,
+            content: line.to_owned(),
+            source,
+        })?;
+        items.push(calories);
+    }
+
+    if !items.is_empty() {
+        elves.push(Elf {
+            index: elves.len(),
+            items,
+        });
+    }
+    Ok(elves)
 }
 
 #[aoc(day1, part1)]
-pub fn solver_part1(input: &[u32]) -> u32 {
-    let mut vec = input.to_owned();
-    vec.sort_unstable();
-    *vec.last().unwrap()
+pub fn solver_part1(input: &[Elf]) -> Option<u64> {
+    top_n(input, 1).first().map(|x| x.total())
 }
 
 #[aoc(day1, part2)]
-pub fn solver_part2(input: &[u32]) -> u32 {
-    let mut vec = input.to_owned();
-    vec.sort_unstable();
-    let end = vec.len();
-    vec.split_off(end - 3)
-        .into_iter()
-        .reduce(|a, b| a + b)
-        .unwrap()
+pub fn solver_part2(input: &[Elf]) -> u64 {
+    top_n(input, 3).into_iter().map(Elf::total).sum()
+}
+
+#[aoc(day1, part1, Report)]
+pub fn solver_report(input: &[Elf]) -> Option<CalorieStats> {
+    CalorieStats::from_elves(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    const EXAMPLE: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000";
+
+    #[test]
+    fn top_n_test() {
+        let elves = input_generator(EXAMPLE).unwrap();
+        let top: Vec<usize> = top_n(&elves, 3).into_iter().map(|x| x.index).collect();
+        assert_eq!(top, vec![3, 2, 4]);
+        assert_eq!(top_n(&elves, 10).len(), 5);
+        assert_eq!(solver_part1(&elves), Some(24000));
+        assert_eq!(solver_part2(&elves), 45000);
+
+        let heavy = input_generator("4294967295\n4294967295").unwrap();
+        assert_eq!(solver_part1(&heavy), Some(8589934590));
+
+        let empty = input_generator("\n").unwrap();
+        assert_eq!(solver_part1(&empty), None);
+        assert_eq!(solver_part2(&empty), 0);
+    }
+
+    #[test]
+    fn stats_test() {
+        let elves = input_generator(EXAMPLE).unwrap();
+        let stats = CalorieStats::from_elves(&elves).unwrap();
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.mean, 11000.0);
+        assert_eq!(stats.median, 10000.0);
+        assert_eq!(stats.percentile(25.0), 6000.0);
+        assert_eq!(stats.percentile(100.0), 24000.0);
+        assert_eq!(stats.std_dev, 6985.699678629192);
+        assert_eq!(
+            stats.histogram(2, 4),
+            "    4000-14000    | #### 4\n   14001-24001    | # 1\n"
+        );
+        assert_eq!(CalorieStats::from_elves(&[]), None);
+    }
+
+    #[test]
+    fn redistribution_test() {
+        let elves = input_generator("5\n5\n5\n\n1\n\n2").unwrap();
+        let expected = vec![Move {
+            calories: 5,
+            from: 0,
+            to: 2,
+        }];
+        assert_eq!(plan_redistribution(&elves, 10), Some(expected.clone()));
+        assert_eq!(plan_redistribution_exact(&elves, 10).unwrap().len(), 1);
+        assert_eq!(plan_redistribution_exact(&elves, 4), None);
+
+        // Keeping the smallest items leaves the 6 with nowhere to go, but moving the 2 works
+        let elves = input_generator("1\n6\n2\n\n3\n\n4").unwrap();
+        assert_eq!(plan_redistribution(&elves, 7), None);
+        assert_eq!(
+            plan_redistribution_exact(&elves, 7),
+            Some(vec![Move {
+                calories: 2,
+                from: 0,
+                to: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn generator_test() {
+        let windows = EXAMPLE.replace('\n', "\r\n");
+        assert_eq!(input_generator(&windows), input_generator(EXAMPLE));
+
+        let padded = input_generator("\n1000\n2000\n\n\n\n3000\n\n").unwrap();
+        assert_eq!(
+            padded,
+            vec![
+                Elf {
+                    index: 0,
+                    items: vec![1000, 2000],
+                },
+                Elf {
+                    index: 1,
+                    items: vec![3000],
+                },
+            ]
+        );
+
+        let error = input_generator("1000\n\n20O0\n3000").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.content, "20O0");
+    }
 }
@@ -1,7 +1,9 @@
 // Day 2: Rock Paper Scissors - https://adventofcode.com/2022/day/2
 //
-// Generator: Just splits the input into lines - we have to parse the input differently depending
-// on the part
+// Generator: Reads the guide line by line into the opponent's `Shape` and a `Token` for the second
+// column, since what the second column means depends on the part. A line with a missing or extra
+// symbol, or a letter that isn't in the guide's mapping, gives back a `GuideError` saying which
+// line was wrong and why, rather than throwing off every round after it.
 //
 // Part 1: Parses the input into a collection of `Matchup` structs. A `Matchup` struct consists of
 // two `Shapes` (rock, paper, or scissors). The `Matchup` struct is used to obtain a score for a
@@ -13,41 +15,138 @@
 // a shape and a desired outcome. We can use the `defeats()` function to determine what shape is
 // required to be thrown for a particular outcome.
 //
-use std::str::FromStr;
+// Cyclic games: Rock paper scissors is the smallest cyclic tournament. Put the shapes in a circle,
+// and every shape beats the (n - 1) / 2 shapes just before it and loses to the (n - 1) / 2 shapes
+// just after it. With an odd number of shapes, every pair of different shapes has exactly one
+// winner, so the whole win/lose relation falls out of the positions. `Shape` uses this to work out
+// what it defeats, and `CyclicGame` uses it for any odd number of named shapes (Rock Paper Scissors
+// Lizard Spock, RPS-7, RPS-101, ...), with scores configurable per shape and per outcome.
+//
+// Mappings: Which letters mean what is kept in a `GuideMapping` rather than being baked into the
+// solvers, so guides with other encodings can be scored the same way. Since the meaning of the
+// second column was only ever a guess, `best_response_mapping` tries all six ways of assigning
+// rock, paper and scissors to the second column's letters and keeps the one that scores highest.
+//
+// Tournaments: Anything implementing `Strategy` picks a shape each round, looking at the rounds
+// played so far in the match. Every strategy plays every other strategy once for the same number
+// of rounds, starting from a clean slate each match, and the `Leaderboard` ranks them by the total
+// score they collected (the same score as part 1) across all of their matches.
+//
+// Breakdown: For checking a guide by hand, the same matchups that get summed up for the answer can
+// instead be turned into one `RoundRecord` per round, showing where each point came from and the
+// score so far. A `Breakdown` prints these as a table, or writes them out as CSV. Running either
+// part as `Breakdown` prints the table for that part's reading of the guide, and running it as
+// `Csv` prints the CSV instead.
+//
+// Constrained play: When the opponent's moves are known, the only thing linking the rounds together
+// is how many of them we've won. So we build a table of the best score for every (round, wins so
+// far) pair: each round we either win (moving to one more win) or take the better of drawing and
+// losing (staying on the same number of wins). Remembering which shape got picked for each entry
+// lets us walk back from any final win count to the moves that got there. With the best score for
+// every exact number of wins, reaching a target with the fewest wins, or scoring as much as possible
+// without winning too often, are just a search over the last row.
+//
+use std::{cmp::Reverse, error::Error, fmt, str::FromStr};
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Shape {
     Rock,
     Paper,
     Scissors,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Outcome {
     Loss,
     Draw,
     Win,
 }
 
+impl Outcome {
+    fn points(self) -> u32 {
+        match self {
+            Outcome::Loss => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSymbolError {
+    pub symbol: String,
+    // What the symbol was supposed to be
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ParseSymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is not a valid {}", self.symbol, self.expected)
+    }
+}
+
+impl Error for ParseSymbolError {}
+
 impl FromStr for Outcome {
-    type Err = ();
+    type Err = ParseSymbolError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "X" => Ok(Self::Loss),
             "Y" => Ok(Self::Draw),
             "Z" => Ok(Self::Win),
-            _ => Err(()),
+            _ => Err(ParseSymbolError {
+                symbol: s.to_owned(),
+                expected: "outcome (X, Y or Z)",
+            }),
+        }
+    }
+}
+
+// The second column of the strategy guide, which means something different in each part
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Token {
+    X,
+    Y,
+    Z,
+}
+
+impl Token {
+    const ALL: [Token; 3] = [Token::X, Token::Y, Token::Z];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl FromStr for Token {
+    type Err = ParseSymbolError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "X" => Ok(Self::X),
+            "Y" => Ok(Self::Y),
+            "Z" => Ok(Self::Z),
+            _ => Err(ParseSymbolError {
+                symbol: s.to_owned(),
+                expected: "token (X, Y or Z)",
+            }),
         }
     }
 }
 
 impl Shape {
+    // In the order they sit around the circle, each shape beating the one before it
+    const ALL: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
     fn defeats(self) -> Self {
-        match self {
-            Shape::Rock => Shape::Scissors,
-            Shape::Paper => Shape::Rock,
-            Shape::Scissors => Shape::Paper,
-        }
+        Shape::ALL[(self.index() + 2) % 3]
+    }
+
+    fn loses_to(self) -> Self {
+        Shape::ALL[(self.index() + 1) % 3]
     }
 
     fn defeated_by(self, opponent: Shape) -> bool {
@@ -74,13 +173,16 @@ impl Shape {
 }
 
 impl FromStr for Shape {
-    type Err = ();
+    type Err = ParseSymbolError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "X" | "A" => Ok(Self::Rock),
             "Y" | "B" => Ok(Self::Paper),
             "Z" | "C" => Ok(Self::Scissors),
-            _ => Err(()),
+            _ => Err(ParseSymbolError {
+                symbol: s.to_owned(),
+                expected: "shape (A, B, C, X, Y or Z)",
+            }),
         }
     }
 }
@@ -94,11 +196,7 @@ impl Matchup {
         let our_throw = self.1;
 
         let outcome = our_throw.get_outcome(enemy_throw);
-        match outcome {
-            Outcome::Loss => our_throw.value(),
-            Outcome::Draw => our_throw.value() + 3,
-            Outcome::Win => our_throw.value() + 6,
-        }
+        our_throw.value() + outcome.points()
     }
 }
 
@@ -110,42 +208,767 @@ impl GamePlan {
         match self.1 {
             Outcome::Loss => their_throw.defeats(),
             Outcome::Draw => their_throw,
-            Outcome::Win => their_throw.defeats().defeats(),
+            Outcome::Win => their_throw.loses_to(),
         }
     }
 }
 
-#[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input.split_whitespace().map(|x| x.to_owned()).collect()
+#[derive(Debug, PartialEq, Eq)]
+pub enum GameError {
+    // A cyclic game needs an odd number of shapes, and at least three of them
+    BadShapeCount(usize),
+    // The number of scores given doesn't match the number of shapes
+    ScoreCountMismatch { shapes: usize, scores: usize },
 }
 
-#[aoc(day2, part1)]
-pub fn solver_part1(input: &[String]) -> u32 {
-    input
-        .chunks(2)
-        .map(|x| {
-            Matchup(
-                Shape::from_str(&x[0]).unwrap(),
-                Shape::from_str(&x[1]).unwrap(),
-            )
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::BadShapeCount(count) => {
+                write!(
+                    f,
+                    "a cyclic game needs an odd number of shapes (at least 3), got {count}"
+                )
+            }
+            GameError::ScoreCountMismatch { shapes, scores } => {
+                write!(f, "got {scores} scores for {shapes} shapes")
+            }
+        }
+    }
+}
+
+impl Error for GameError {}
+
+// A game where any odd number of shapes sit in a circle, identified by their position. Each shape
+// beats the half of the circle just before it
+#[derive(Debug, Clone)]
+pub struct CyclicGame {
+    names: Vec<String>,
+    shape_scores: Vec<u32>,
+    // Points for a loss, a draw and a win
+    outcome_scores: [u32; 3],
+}
+
+impl CyclicGame {
+    // Shapes score their position in the circle plus one, outcomes score like the puzzle does
+    pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Result<Self, GameError> {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        if names.len() < 3 || names.len().is_multiple_of(2) {
+            return Err(GameError::BadShapeCount(names.len()));
+        }
+        Ok(CyclicGame {
+            shape_scores: (1..=names.len() as u32).collect(),
+            names,
+            outcome_scores: [0, 3, 6],
         })
+    }
+
+    // A game with `count` shapes, just named by their number (like RPS-101)
+    pub fn numbered(count: usize) -> Result<Self, GameError> {
+        CyclicGame::new((1..=count).map(|x| x.to_string()))
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        CyclicGame::new(["Rock", "Paper", "Scissors"]).unwrap()
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        CyclicGame::new(["Rock", "Spock", "Paper", "Lizard", "Scissors"]).unwrap()
+    }
+
+    pub fn rps7() -> Self {
+        CyclicGame::new([
+            "Water", "Air", "Paper", "Sponge", "Scissors", "Fire", "Rock",
+        ])
+        .unwrap()
+    }
+
+    pub fn with_shape_scores(mut self, scores: Vec<u32>) -> Result<Self, GameError> {
+        if scores.len() != self.names.len() {
+            return Err(GameError::ScoreCountMismatch {
+                shapes: self.names.len(),
+                scores: scores.len(),
+            });
+        }
+        self.shape_scores = scores;
+        Ok(self)
+    }
+
+    pub fn with_outcome_scores(mut self, loss: u32, draw: u32, win: u32) -> Self {
+        self.outcome_scores = [loss, draw, win];
+        self
+    }
+
+    pub fn shape_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, shape: usize) -> &str {
+        &self.names[shape]
+    }
+
+    pub fn shape_by_name(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|x| x.eq_ignore_ascii_case(name))
+    }
+
+    // Whether `shape` beats `opponent`, i.e. the opponent sits in the half of the circle just before
+    pub fn beats(&self, shape: usize, opponent: usize) -> bool {
+        let count = self.shape_count();
+        let distance = (shape + count - opponent) % count;
+        (1..=count / 2).contains(&distance)
+    }
+
+    pub fn outcome(&self, ours: usize, theirs: usize) -> Outcome {
+        if ours == theirs {
+            Outcome::Draw
+        } else if self.beats(ours, theirs) {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    pub fn score(&self, theirs: usize, ours: usize) -> u32 {
+        let outcome_score = match self.outcome(ours, theirs) {
+            Outcome::Loss => self.outcome_scores[0],
+            Outcome::Draw => self.outcome_scores[1],
+            Outcome::Win => self.outcome_scores[2],
+        };
+        self.shape_scores[ours] + outcome_score
+    }
+
+    // Every shape we could throw to get `outcome` against `theirs`
+    pub fn responses(&self, theirs: usize, outcome: Outcome) -> impl Iterator<Item = usize> + '_ {
+        (0..self.shape_count()).filter(move |x| self.outcome(*x, theirs) == outcome)
+    }
+
+    // The highest scoring shape that gets `outcome` against `theirs`
+    pub fn best_response(&self, theirs: usize, outcome: Outcome) -> usize {
+        self.responses(theirs, outcome)
+            .max_by_key(|x| self.shape_scores[*x])
+            .unwrap()
+    }
+}
+
+// Every way of ordering rock, paper and scissors
+const SHAPE_PERMUTATIONS: [[Shape; 3]; 6] = [
+    [Shape::Rock, Shape::Paper, Shape::Scissors],
+    [Shape::Rock, Shape::Scissors, Shape::Paper],
+    [Shape::Paper, Shape::Rock, Shape::Scissors],
+    [Shape::Paper, Shape::Scissors, Shape::Rock],
+    [Shape::Scissors, Shape::Rock, Shape::Paper],
+    [Shape::Scissors, Shape::Paper, Shape::Rock],
+];
+
+// What the letters in a strategy guide mean
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuideMapping {
+    // The opponent's letters for rock, paper and scissors
+    opponent_symbols: [String; 3],
+    // The letters used in the second column, for the X, Y and Z tokens
+    second_symbols: [String; 3],
+    // The shape each token stands for, when it's read as our response
+    responses: [Shape; 3],
+    // The outcome each token stands for, when it's read as a desired outcome
+    outcomes: [Outcome; 3],
+}
+
+impl Default for GuideMapping {
+    fn default() -> Self {
+        GuideMapping::new(["A", "B", "C"], ["X", "Y", "Z"])
+    }
+}
+
+impl GuideMapping {
+    // The second column's letters stand for rock, paper and scissors, or a loss, draw and win
+    pub fn new(opponent_symbols: [&str; 3], second_symbols: [&str; 3]) -> Self {
+        GuideMapping {
+            opponent_symbols: opponent_symbols.map(str::to_owned),
+            second_symbols: second_symbols.map(str::to_owned),
+            responses: Shape::ALL,
+            outcomes: [Outcome::Loss, Outcome::Draw, Outcome::Win],
+        }
+    }
+
+    // Change which shape each of the second column's letters stands for
+    pub fn with_responses(mut self, responses: [Shape; 3]) -> Self {
+        self.responses = responses;
+        self
+    }
+
+    pub fn opponent_shape(&self, symbol: &str) -> Option<Shape> {
+        let position = self.opponent_symbols.iter().position(|x| x == symbol)?;
+        Some(Shape::ALL[position])
+    }
+
+    pub fn token(&self, symbol: &str) -> Option<Token> {
+        let position = self.second_symbols.iter().position(|x| x == symbol)?;
+        Some(Token::ALL[position])
+    }
+
+    pub fn response_shape(&self, token: Token) -> Shape {
+        self.responses[token.index()]
+    }
+
+    pub fn outcome(&self, token: Token) -> Outcome {
+        self.outcomes[token.index()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuideErrorKind {
+    // The line only has the opponent's shape
+    MissingToken,
+    // The line has something after the second column
+    ExtraToken(String),
+    UnknownSymbol(ParseSymbolError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuideError {
+    // 1-based line number of the offending line
+    pub line: usize,
+    pub kind: GuideErrorKind,
+}
+
+impl fmt::Display for GuideError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            GuideErrorKind::MissingToken => write!(f, "expected two symbols, got one"),
+            GuideErrorKind::ExtraToken(token) => write!(f, "unexpected extra symbol {token:?}"),
+            GuideErrorKind::UnknownSymbol(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for GuideError {}
+
+// Read a strategy guide line by line, using `mapping` to make sense of the letters. Blank lines
+// are skipped
+pub fn parse_guide(input: &str, mapping: &GuideMapping) -> Result<Vec<(Shape, Token)>, GuideError> {
+    let mut guide = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let error = |kind| GuideError {
+            line: number + 1,
+            kind,
+        };
+        let unknown = |symbol: &str, expected| {
+            error(GuideErrorKind::UnknownSymbol(ParseSymbolError {
+                symbol: symbol.to_owned(),
+                expected,
+            }))
+        };
+
+        let mut symbols = line.split_whitespace();
+        let Some(first) = symbols.next() else {
+            continue;
+        };
+        let second = symbols
+            .next()
+            .ok_or_else(|| error(GuideErrorKind::MissingToken))?;
+        if let Some(extra) = symbols.next() {
+            return Err(error(GuideErrorKind::ExtraToken(extra.to_owned())));
+        }
+
+        let shape = mapping
+            .opponent_shape(first)
+            .ok_or_else(|| unknown(first, "opponent shape"))?;
+        let token = mapping
+            .token(second)
+            .ok_or_else(|| unknown(second, "second column symbol"))?;
+        guide.push((shape, token));
+    }
+    Ok(guide)
+}
+
+// Read a guide's second column as the shape we should throw
+fn response_matchups<'a>(
+    input: &'a [(Shape, Token)],
+    mapping: &'a GuideMapping,
+) -> impl Iterator<Item = Matchup> + 'a {
+    input
+        .iter()
+        .map(|x| Matchup(x.0, mapping.response_shape(x.1)))
+}
+
+// Read a guide's second column as how the round needs to end
+fn outcome_matchups<'a>(
+    input: &'a [(Shape, Token)],
+    mapping: &'a GuideMapping,
+) -> impl Iterator<Item = Matchup> + 'a {
+    input.iter().map(|x| {
+        let plan = GamePlan(x.0, mapping.outcome(x.1));
+        Matchup(plan.0, plan.get_move())
+    })
+}
+
+// Score a guide, reading the second column as the shape we should throw
+pub fn score_responses(input: &[(Shape, Token)], mapping: &GuideMapping) -> u32 {
+    response_matchups(input, mapping)
         .map(|x| x.get_score())
         .sum()
 }
 
-#[aoc(day2, part2)]
-pub fn solver_part2(input: &[String]) -> u32 {
-    input
-        .chunks(2)
+// Score a guide, reading the second column as how the round needs to end
+pub fn score_outcomes(input: &[(Shape, Token)], mapping: &GuideMapping) -> u32 {
+    outcome_matchups(input, mapping)
+        .map(|x| x.get_score())
+        .sum()
+}
+
+// Try every assignment of shapes to the second column's letters, and return the one giving the
+// highest score along with that score
+pub fn best_response_mapping(
+    input: &[(Shape, Token)],
+    mapping: &GuideMapping,
+) -> (GuideMapping, u32) {
+    SHAPE_PERMUTATIONS
+        .into_iter()
         .map(|x| {
-            GamePlan(
-                Shape::from_str(&x[0]).unwrap(),
-                Outcome::from_str(&x[1]).unwrap(),
-            )
+            let candidate = mapping.clone().with_responses(x);
+            let score = score_responses(input, &candidate);
+            (candidate, score)
         })
-        .map(|x| Matchup(x.0, x.get_move()).get_score())
-        .sum()
+        .max_by_key(|x| x.1)
+        .unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundRecord {
+    // Starting from 1
+    pub round: usize,
+    pub opponent: Shape,
+    pub ours: Shape,
+    pub outcome: Outcome,
+    pub shape_points: u32,
+    pub outcome_points: u32,
+    pub running_total: u32,
+}
+
+fn breakdown(matchups: impl Iterator<Item = Matchup>) -> impl Iterator<Item = RoundRecord> {
+    matchups
+        .enumerate()
+        .scan(0, |running_total, (index, matchup)| {
+            let Matchup(opponent, ours) = matchup;
+            let outcome = ours.get_outcome(opponent);
+            *running_total += matchup.get_score();
+            Some(RoundRecord {
+                round: index + 1,
+                opponent,
+                ours,
+                outcome,
+                shape_points: ours.value(),
+                outcome_points: outcome.points(),
+                running_total: *running_total,
+            })
+        })
+}
+
+// Go through a guide round by round, reading the second column as the shape we should throw
+pub fn breakdown_responses<'a>(
+    input: &'a [(Shape, Token)],
+    mapping: &'a GuideMapping,
+) -> impl Iterator<Item = RoundRecord> + 'a {
+    breakdown(response_matchups(input, mapping))
+}
+
+// Go through a guide round by round, reading the second column as how the round needs to end
+pub fn breakdown_outcomes<'a>(
+    input: &'a [(Shape, Token)],
+    mapping: &'a GuideMapping,
+) -> impl Iterator<Item = RoundRecord> + 'a {
+    breakdown(outcome_matchups(input, mapping))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown(pub Vec<RoundRecord>);
+
+impl Breakdown {
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("round,opponent,ours,outcome,shape_points,outcome_points,running_total\n");
+        for record in &self.0 {
+            csv += &format!(
+                "{},{:?},{:?},{:?},{},{},{}\n",
+                record.round,
+                record.opponent,
+                record.ours,
+                record.outcome,
+                record.shape_points,
+                record.outcome_points,
+                record.running_total
+            );
+        }
+        csv
+    }
+}
+
+// Displays a `Breakdown` as CSV rather than a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakdownCsv(pub Breakdown);
+
+impl fmt::Display for BreakdownCsv {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_csv())
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:<8}  {:<8}  {:<7}  {:>5}  {:>7}  {:>7}",
+            "round", "opponent", "ours", "outcome", "shape", "outcome", "total"
+        )?;
+        for record in &self.0 {
+            writeln!(
+                f,
+                "{:>5}  {:<8}  {:<8}  {:<7}  {:>5}  {:>7}  {:>7}",
+                record.round,
+                format!("{:?}", record.opponent),
+                format!("{:?}", record.ours),
+                format!("{:?}", record.outcome),
+                record.shape_points,
+                record.outcome_points,
+                record.running_total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub moves: Vec<Shape>,
+    pub score: u32,
+    pub wins: usize,
+}
+
+struct WinTable {
+    // The shape thrown in each round for the best score ending that round on each number of wins
+    choices: Vec<Vec<Option<Shape>>>,
+    // The best score after every round for each exact number of wins, if it can be done at all
+    best: Vec<Option<u32>>,
+}
+
+impl WinTable {
+    fn new(opponents: &[Shape]) -> Self {
+        let mut best = vec![None; opponents.len() + 1];
+        best[0] = Some(0);
+        let mut choices = Vec::with_capacity(opponents.len());
+
+        for (round, opponent) in opponents.iter().enumerate() {
+            let score = |ours: Shape| Matchup(*opponent, ours).get_score();
+            let win = opponent.loses_to();
+            let no_win = [*opponent, opponent.defeats()]
+                .into_iter()
+                .max_by_key(|x| score(*x))
+                .unwrap();
+
+            let mut next = vec![None; opponents.len() + 1];
+            let mut choice = vec![None; opponents.len() + 1];
+            for (wins, current) in best.iter().enumerate().take(round + 1) {
+                let Some(current) = *current else {
+                    continue;
+                };
+                for (ours, wins_after) in [(no_win, wins), (win, wins + 1)] {
+                    if next[wins_after] < Some(current + score(ours)) {
+                        next[wins_after] = Some(current + score(ours));
+                        choice[wins_after] = Some(ours);
+                    }
+                }
+            }
+            best = next;
+            choices.push(choice);
+        }
+        WinTable { choices, best }
+    }
+
+    // Walk back through the choices to find the moves that end on exactly `wins` wins
+    fn plan(&self, opponents: &[Shape], wins: usize) -> Option<Plan> {
+        let score = self.best[wins]?;
+        let mut moves = Vec::with_capacity(opponents.len());
+        let mut remaining = wins;
+        for (round, opponent) in opponents.iter().enumerate().rev() {
+            let ours = self.choices[round][remaining].unwrap();
+            if ours.get_outcome(*opponent) == Outcome::Win {
+                remaining -= 1;
+            }
+            moves.push(ours);
+        }
+        moves.reverse();
+        Some(Plan { moves, score, wins })
+    }
+}
+
+// Find moves that score at least `target` against the given opponent moves, winning as few rounds
+// as possible
+pub fn fewest_wins_for_target(opponents: &[Shape], target: u32) -> Option<Plan> {
+    let table = WinTable::new(opponents);
+    let wins = table.best.iter().position(|x| *x >= Some(target))?;
+    table.plan(opponents, wins)
+}
+
+// Find moves that score as much as possible against the given opponent moves, without winning
+// more than `max_wins` rounds
+pub fn best_score_with_win_cap(opponents: &[Shape], max_wins: usize) -> Plan {
+    let table = WinTable::new(opponents);
+    let wins = (0..=max_wins.min(opponents.len()))
+        .max_by_key(|x| (table.best[*x], Reverse(*x)))
+        .unwrap();
+    table.plan(opponents, wins).unwrap()
+}
+
+// A round from one player's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub ours: Shape,
+    pub theirs: Shape,
+}
+
+pub trait Strategy {
+    fn name(&self) -> String;
+
+    // Pick a shape for the next round, knowing every round played so far in this match
+    fn next_move(&mut self, history: &[Round]) -> Shape;
+
+    // Forget anything about the last match
+    fn reset(&mut self) {}
+}
+
+// Always throw the same shape
+pub struct Fixed(pub Shape);
+
+impl Strategy for Fixed {
+    fn name(&self) -> String {
+        format!("always {:?}", self.0)
+    }
+
+    fn next_move(&mut self, _history: &[Round]) -> Shape {
+        self.0
+    }
+}
+
+// Throw whatever the strategy guide says, starting over once it runs out
+pub struct EncryptedGuide {
+    plays: Vec<Shape>,
+}
+
+impl EncryptedGuide {
+    // Read our plays from the guide's second column, the way part 1 does. Gives `None` for an empty
+    // guide, since there'd be nothing to play
+    pub fn from_guide(input: &[(Shape, Token)], mapping: &GuideMapping) -> Option<Self> {
+        if input.is_empty() {
+            return None;
+        }
+        Some(EncryptedGuide {
+            plays: input.iter().map(|x| mapping.response_shape(x.1)).collect(),
+        })
+    }
+}
+
+impl Strategy for EncryptedGuide {
+    fn name(&self) -> String {
+        "encrypted guide".to_owned()
+    }
+
+    fn next_move(&mut self, history: &[Round]) -> Shape {
+        self.plays[history.len() % self.plays.len()]
+    }
+}
+
+// Throw a random shape, from an xorshift generator so the same seed always plays the same way
+pub struct SeededRandom {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on zero
+        let seed = seed.max(1);
+        SeededRandom { seed, state: seed }
+    }
+}
+
+impl Strategy for SeededRandom {
+    fn name(&self) -> String {
+        format!("random (seed {})", self.seed)
+    }
+
+    fn next_move(&mut self, _history: &[Round]) -> Shape {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        Shape::ALL[(self.state % 3) as usize]
+    }
+
+    fn reset(&mut self) {
+        self.state = self.seed;
+    }
+}
+
+// Beat whatever the opponent has thrown most often so far
+pub struct CounterFrequent;
+
+impl Strategy for CounterFrequent {
+    fn name(&self) -> String {
+        "counter most frequent".to_owned()
+    }
+
+    fn next_move(&mut self, history: &[Round]) -> Shape {
+        let mut counts = [0; 3];
+        for round in history {
+            counts[round.theirs.index()] += 1;
+        }
+        // `max_by_key` picks the last of any ties, so go backwards to prefer rock
+        let favourite = Shape::ALL
+            .into_iter()
+            .rev()
+            .max_by_key(|x| counts[x.index()])
+            .unwrap();
+        favourite.loses_to()
+    }
+}
+
+// Beat whatever the opponent threw last round, starting with rock
+pub struct BeatLast;
+
+impl Strategy for BeatLast {
+    fn name(&self) -> String {
+        "beat last move".to_owned()
+    }
+
+    fn next_move(&mut self, history: &[Round]) -> Shape {
+        history.last().map_or(Shape::Rock, |x| x.theirs.loses_to())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Standing {
+    pub name: String,
+    pub score: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Standing {
+    fn record(&mut self, round: Round) {
+        self.score += Matchup(round.theirs, round.ours).get_score();
+        match round.ours.get_outcome(round.theirs) {
+            Outcome::Loss => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Win => self.wins += 1,
+        }
+    }
+}
+
+// Standings sorted from the highest score to the lowest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaderboard(pub Vec<Standing>);
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<24} {:>8} {:>6} {:>6} {:>6}",
+            "rank", "strategy", "score", "wins", "draws", "losses"
+        )?;
+        for (rank, standing) in self.0.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<24} {:>8} {:>6} {:>6} {:>6}",
+                rank + 1,
+                standing.name,
+                standing.score,
+                standing.wins,
+                standing.draws,
+                standing.losses
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Play every strategy against every other one for `rounds` rounds
+pub fn run_tournament(strategies: &mut [Box<dyn Strategy>], rounds: usize) -> Leaderboard {
+    let mut standings: Vec<Standing> = strategies
+        .iter()
+        .map(|x| Standing {
+            name: x.name(),
+            ..Default::default()
+        })
+        .collect();
+
+    for second in 1..strategies.len() {
+        for first in 0..second {
+            let (left, right) = strategies.split_at_mut(second);
+            let (player_a, player_b) = (&mut left[first], &mut right[0]);
+            player_a.reset();
+            player_b.reset();
+
+            let mut history_a = Vec::with_capacity(rounds);
+            let mut history_b = Vec::with_capacity(rounds);
+            for _ in 0..rounds {
+                let (throw_a, throw_b) = (
+                    player_a.next_move(&history_a),
+                    player_b.next_move(&history_b),
+                );
+                let round_a = Round {
+                    ours: throw_a,
+                    theirs: throw_b,
+                };
+                let round_b = Round {
+                    ours: throw_b,
+                    theirs: throw_a,
+                };
+                standings[first].record(round_a);
+                standings[second].record(round_b);
+                history_a.push(round_a);
+                history_b.push(round_b);
+            }
+        }
+    }
+
+    standings.sort_by_key(|x| Reverse(x.score));
+    Leaderboard(standings)
+}
+
+#[aoc_generator(day2)]
+pub fn input_generator(input: &str) -> Result<Vec<(Shape, Token)>, GuideError> {
+    parse_guide(input, &GuideMapping::default())
+}
+
+#[aoc(day2, part1)]
+pub fn solver_part1(input: &[(Shape, Token)]) -> u32 {
+    score_responses(input, &GuideMapping::default())
+}
+
+#[aoc(day2, part2)]
+pub fn solver_part2(input: &[(Shape, Token)]) -> u32 {
+    score_outcomes(input, &GuideMapping::default())
+}
+
+#[aoc(day2, part1, Breakdown)]
+pub fn solver_part1_breakdown(input: &[(Shape, Token)]) -> Breakdown {
+    Breakdown(breakdown_responses(input, &GuideMapping::default()).collect())
+}
+
+#[aoc(day2, part2, Breakdown)]
+pub fn solver_part2_breakdown(input: &[(Shape, Token)]) -> Breakdown {
+    Breakdown(breakdown_outcomes(input, &GuideMapping::default()).collect())
+}
+
+#[aoc(day2, part1, Csv)]
+pub fn solver_part1_csv(input: &[(Shape, Token)]) -> BreakdownCsv {
+    BreakdownCsv(solver_part1_breakdown(input))
+}
+
+#[aoc(day2, part2, Csv)]
+pub fn solver_part2_csv(input: &[(Shape, Token)]) -> BreakdownCsv {
+    BreakdownCsv(solver_part2_breakdown(input))
 }
 
 #[cfg(test)]
@@ -160,4 +983,194 @@ mod tests {
         let matchup = Matchup(Shape::Scissors, Shape::Scissors);
         assert_eq!(matchup.get_score(), 6);
     }
+
+    #[test]
+    fn mapping_test() {
+        let input = input_generator("A Y\nB X\nC Z").unwrap();
+        assert_eq!(solver_part1(&input), 15);
+        assert_eq!(solver_part2(&input), 12);
+
+        let mapping = GuideMapping::new(["R", "P", "S"], ["1", "2", "3"]);
+        let renamed = parse_guide("R 2\nP 1\nS 3", &mapping).unwrap();
+        assert_eq!(score_responses(&renamed, &mapping), 15);
+        assert_eq!(score_outcomes(&renamed, &mapping), 12);
+
+        let (best, score) = best_response_mapping(&input, &GuideMapping::default());
+        assert_eq!(score, 24);
+        assert_eq!(best.response_shape(Token::X), Shape::Scissors);
+        assert_eq!(best.response_shape(Token::Y), Shape::Paper);
+        assert_eq!(best.response_shape(Token::Z), Shape::Rock);
+    }
+
+    #[test]
+    fn generator_test() {
+        assert_eq!(
+            input_generator("A Y\r\n\nC Z\n"),
+            Ok(vec![(Shape::Rock, Token::Y), (Shape::Scissors, Token::Z)])
+        );
+        assert_eq!(
+            input_generator("A Y\nB\nC Z"),
+            Err(GuideError {
+                line: 2,
+                kind: GuideErrorKind::MissingToken,
+            })
+        );
+        assert_eq!(
+            input_generator("A Y Z").unwrap_err().kind,
+            GuideErrorKind::ExtraToken("Z".to_owned())
+        );
+        assert_eq!(
+            input_generator("A Y\nB X\nD Z").unwrap_err().to_string(),
+            "line 3: \"D\" is not a valid opponent shape"
+        );
+        assert_eq!(Shape::from_str("Q").unwrap_err().symbol, "Q".to_owned());
+    }
+
+    #[test]
+    fn breakdown_test() {
+        let input = input_generator("A Y\nB X\nC Z").unwrap();
+        let mapping = GuideMapping::default();
+        let records = Breakdown(breakdown_outcomes(&input, &mapping).collect());
+        assert_eq!(
+            records.0[1],
+            RoundRecord {
+                round: 2,
+                opponent: Shape::Paper,
+                ours: Shape::Rock,
+                outcome: Outcome::Loss,
+                shape_points: 1,
+                outcome_points: 0,
+                running_total: 5,
+            }
+        );
+        assert_eq!(records.0[2].running_total, solver_part2(&input));
+        assert_eq!(solver_part2_breakdown(&input), records);
+        assert_eq!(solver_part2_csv(&input).to_string(), records.to_csv());
+        assert_eq!(
+            solver_part1_breakdown(&input).0[2].running_total,
+            solver_part1(&input)
+        );
+        assert_eq!(
+            breakdown_responses(&input, &mapping)
+                .last()
+                .unwrap()
+                .running_total,
+            solver_part1(&input)
+        );
+        assert_eq!(
+            records.to_csv().lines().nth(3),
+            Some("3,Scissors,Rock,Win,1,6,12")
+        );
+        assert_eq!(
+            records.to_string().lines().nth(1),
+            Some("    1  Rock      Rock      Draw         1        3        4")
+        );
+    }
+
+    #[test]
+    fn constrained_play_test() {
+        let guide = input_generator("A Y\nB X\nC Z").unwrap();
+        let opponents: Vec<Shape> = guide.iter().map(|x| x.0).collect();
+
+        let plan = fewest_wins_for_target(&opponents, 16).unwrap();
+        assert_eq!(plan.wins, 1);
+        assert_eq!(plan.score, 19);
+        assert_eq!(
+            plan.moves,
+            vec![Shape::Rock, Shape::Scissors, Shape::Scissors]
+        );
+        assert_eq!(fewest_wins_for_target(&opponents, 15).unwrap().wins, 0);
+        assert_eq!(fewest_wins_for_target(&opponents, 25), None);
+
+        let plan = best_score_with_win_cap(&opponents, 2);
+        assert_eq!(plan.score, 23);
+        let total: u32 = opponents
+            .iter()
+            .zip(&plan.moves)
+            .map(|(theirs, ours)| Matchup(*theirs, *ours).get_score())
+            .sum();
+        assert_eq!(total, plan.score);
+        assert_eq!(best_score_with_win_cap(&opponents, 10).score, 24);
+    }
+
+    #[test]
+    fn tournament_test() {
+        let mut random = SeededRandom::new(2022);
+        let first: Vec<Shape> = (0..10).map(|_| random.next_move(&[])).collect();
+        random.reset();
+        let second: Vec<Shape> = (0..10).map(|_| random.next_move(&[])).collect();
+        assert_eq!(first, second);
+
+        let guide = input_generator("A Y\nB X\nC Z").unwrap();
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Fixed(Shape::Rock)),
+            Box::new(BeatLast),
+            Box::new(CounterFrequent),
+            Box::new(EncryptedGuide::from_guide(&guide, &GuideMapping::default()).unwrap()),
+        ];
+        let leaderboard = run_tournament(&mut strategies[..3], 10);
+        let names: Vec<&str> = leaderboard.0.iter().map(|x| x.name.as_str()).collect();
+        // Both adaptive strategies take the rock player apart
+        assert_eq!(
+            names,
+            vec!["beat last move", "counter most frequent", "always Rock"]
+        );
+        assert_eq!(leaderboard.0[2].losses, 19);
+
+        assert!(EncryptedGuide::from_guide(&[], &GuideMapping::default()).is_none());
+
+        let leaderboard = run_tournament(&mut strategies, 3);
+        assert_eq!(leaderboard.0.len(), 4);
+        assert_eq!(
+            leaderboard.0.iter().map(|x| x.wins).sum::<u32>(),
+            leaderboard.0.iter().map(|x| x.losses).sum::<u32>()
+        );
+    }
+
+    #[test]
+    fn cyclic_game_test() {
+        let game = CyclicGame::rock_paper_scissors();
+        for theirs in Shape::ALL {
+            for ours in Shape::ALL {
+                assert_eq!(
+                    game.score(theirs.index(), ours.index()),
+                    Matchup(theirs, ours).get_score()
+                );
+            }
+            for outcome in [Outcome::Loss, Outcome::Draw, Outcome::Win] {
+                let plan = GamePlan(theirs, outcome);
+                assert_eq!(
+                    game.best_response(theirs.index(), outcome),
+                    plan.get_move().index()
+                );
+            }
+        }
+
+        let game = CyclicGame::rock_paper_scissors_lizard_spock();
+        let shape = |name| game.shape_by_name(name).unwrap();
+        for (winner, loser) in [
+            ("Scissors", "Paper"),
+            ("Paper", "Rock"),
+            ("Rock", "Lizard"),
+            ("Lizard", "Spock"),
+            ("Spock", "Scissors"),
+            ("Scissors", "Lizard"),
+            ("Lizard", "Paper"),
+            ("Paper", "Spock"),
+            ("Spock", "Rock"),
+            ("Rock", "Scissors"),
+        ] {
+            assert!(game.beats(shape(winner), shape(loser)));
+            assert!(!game.beats(shape(loser), shape(winner)));
+        }
+
+        let game = CyclicGame::numbered(101).unwrap();
+        for shape in 0..101 {
+            assert_eq!(game.responses(shape, Outcome::Win).count(), 50);
+        }
+        assert_eq!(
+            CyclicGame::numbered(4).unwrap_err(),
+            GameError::BadShapeCount(4)
+        );
+    }
 }
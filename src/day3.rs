@@ -1,95 +1,617 @@
 // Day 3: Rucksack Reorganization  - https://adventofcode.com/2022/day/3
 //
 // Generator: Parses the input into a `Rucksack` struct, splitting the line in half and putting
-// each half into a string. Makes the assumption that the length of each line is divisble by two.
-// In hindsight, this could have been a tuple struct containing u8 slices since we're only
-// concerned about ASCII characters here.
+// each half into a Vec<Item>. An `Item` can only be made from an ASCII letter, so anything else in
+// the input (or a line that can't be split evenly in half) gives back a `RucksackError` with the
+// line it was found on.
 //
-// Part 1: Gets both halves of the rucksack, converts them into bytes (since we are only concerned
-// about ASCII characters), and checks for intersections between the two sides using iterators. We
-// get the priority by doing some math on ASCII character values, which conveniently line up with
-// the specifications for priority.
+// Part 1: Gets both halves of the rucksack as an `ItemSet`, and intersects them. We get the
+// priority by doing some math on ASCII character values, which conveniently line up with the
+// specifications for priority: lowercase letters count up from 1, and uppercase from 27.
 //
-// Part 2: Gets three rucksacks from the input, combines their halves together, and essentially
-// uses the same method for determining intersections as part 1, except we do it on the first and
-// second rucksack, and compare that intersection with the third to find what the intersection is
-// between all three.
+// Part 2: Gets three rucksacks from the input, takes the union of both halves of each one, and
+// intersects all three to find the item they have in common. The group size is a parameter of
+// `group_badges`, which keeps every item a group has in common rather than just the first one. A
+// short group at the end is still a group, and a group with no badge just has an empty set, so
+// neither of them panic. `badge_diagnostics` picks out the groups that don't have exactly one
+// badge, since those mean the input doesn't look like the puzzle says it should. Rather than give a
+// total that quietly leaves those groups out, part 2 gives back a `BadgeError` for the first one.
 //
-fn get_priority(character: u8) -> u8 {
-    if character > 97 {
-        character - 96
-    } else {
-        character - 38
+// Group discovery: If the rucksacks aren't in their groups already, `discover_groups` searches
+// for a way to split them into groups of K with exactly one badge each. Every rucksack has to end
+// up in some group, so at each step we look for the rucksack with the fewest groups it could still
+// be part of, and try each of those groups in turn (this is the same trick as picking the column
+// with the fewest rows in an exact cover search). If any rucksack has no possible group left, that
+// branch is a dead end and we back up straight away. Only once every option has been tried do we
+// know there is no grouping at all. This can take exponential time on inputs built to be awkward.
+//
+// Reorganization: Fixing a rucksack means every item type ends up in only one compartment. The
+// compartments have to stay the same size, so items can only move by swapping one from each side.
+// That means every type shared between the two sides has to move all of its items over one way or
+// the other, and types only on one side can come along for the ride to even out the swaps. Going
+// through the item types one at a time, we keep the cheapest way (fewest swaps, and then the lowest
+// total priority of the items moved) to reach each difference between the number of items moved
+// each way. The cheapest way of ending on a difference of zero is the plan. Some rucksacks can't be
+// fixed at all (like `aa|ab`), in which case there is no plan.
+//
+// Item sets: There are only 52 item types, so a set of them fits in a u64, with the bit for each
+// item being its priority. Unions and intersections are then a single OR or AND, and the priority
+// of the item in a set is just the index of its bit. This replaces checking every item of one side
+// against every item of the other with `contains`, which also had to collect into a new Vec.
+//
+use std::{collections::HashMap, error::Error, fmt, ops::Range};
+
+// An item type, which is always an ASCII letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Item(u8);
+
+impl Item {
+    pub fn priority(self) -> u8 {
+        if self.0.is_ascii_lowercase() {
+            self.0 - b'a' + 1
+        } else {
+            self.0 - b'A' + 27
+        }
+    }
+
+    pub fn from_priority(priority: u8) -> Option<Self> {
+        match priority {
+            1..=26 => Some(Item(b'a' + priority - 1)),
+            27..=52 => Some(Item(b'A' + priority - 27)),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        char::from(self.0)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidItem(pub char);
+
+impl fmt::Display for InvalidItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is not an item, items are ASCII letters", self.0)
+    }
+}
+
+impl Error for InvalidItem {}
+
+impl TryFrom<char> for Item {
+    type Error = InvalidItem;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        if value.is_ascii_alphabetic() {
+            Ok(Item(value as u8))
+        } else {
+            Err(InvalidItem(value))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RucksackErrorKind {
+    InvalidItem(InvalidItem),
+    // The line has this many items, which can't be split into two equal compartments
+    OddLength(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RucksackError {
+    // 1-based line number of the offending line
+    pub line: usize,
+    pub kind: RucksackErrorKind,
+}
+
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            RucksackErrorKind::InvalidItem(error) => write!(f, "{error}"),
+            RucksackErrorKind::OddLength(length) => write!(
+                f,
+                "{length} items can't be split evenly between two compartments"
+            ),
+        }
+    }
+}
+
+impl Error for RucksackError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn from_items(items: &[Item]) -> Self {
+        ItemSet(items.iter().fold(0, |acc, x| acc | 1 << x.priority()))
+    }
+
+    pub fn union(self, other: ItemSet) -> Self {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: ItemSet) -> Self {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn contains(self, item: Item) -> bool {
+        self.0 & 1 << item.priority() != 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    // The priorities of every item in the set, lowest first
+    pub fn priorities(self) -> impl Iterator<Item = u8> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            let priority = bits.trailing_zeros() as u8;
+            // Clear the lowest set bit
+            bits &= bits.checked_sub(1)?;
+            Some(priority)
+        })
+    }
+
+    pub fn items(self) -> impl Iterator<Item = Item> {
+        self.priorities().filter_map(Item::from_priority)
+    }
+
+    pub fn priority_sum(self) -> u32 {
+        self.priorities().map(u32::from).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rucksack {
-    side_a: String,
-    side_b: String,
+    side_a: Vec<Item>,
+    side_b: Vec<Item>,
 }
 impl Rucksack {
-    fn get_shared(&self) -> u8 {
-        let a_collection = self.side_a.as_bytes();
-        let intersection: Vec<&u8> = self
-            .side_b
-            .as_bytes()
-            .iter()
-            .filter(|x| a_collection.contains(x))
+    fn get_shared(&self) -> ItemSet {
+        ItemSet::from_items(&self.side_a).intersection(ItemSet::from_items(&self.side_b))
+    }
+
+    // Every item type in the rucksack, from either side
+    fn get_items(&self) -> ItemSet {
+        ItemSet::from_items(&self.side_a).union(ItemSet::from_items(&self.side_b))
+    }
+
+    // Work out the fewest swaps between compartments that leave no item type in both of them
+    pub fn plan_reorganization(&self) -> Option<Reorganization> {
+        let count = |side: &[Item], item| side.iter().filter(|x| **x == item).count() as i32;
+
+        // For each difference between items moved from A and items moved from B, the cheapest way
+        // found to get there so far
+        let mut states = HashMap::from([(0, PlanState::default())]);
+
+        for item in self.get_items().items() {
+            let (in_a, in_b) = (count(&self.side_a, item), count(&self.side_b, item));
+            let priority = u32::from(item.priority());
+
+            let mut options = vec![];
+            if in_a == 0 || in_b == 0 {
+                options.push((Direction::Stay, 0));
+            }
+            if in_a > 0 {
+                options.push((Direction::ToB, in_a));
+            }
+            if in_b > 0 {
+                options.push((Direction::ToA, -in_b));
+            }
+
+            let mut next: HashMap<i32, PlanState> = HashMap::new();
+            for (difference, state) in &states {
+                for (direction, change) in &options {
+                    let swaps = state.swaps + change.max(&0);
+                    let cost = state.cost + change.unsigned_abs() * priority;
+                    if next
+                        .get(&(difference + change))
+                        .is_none_or(|x| (swaps, cost) < (x.swaps, x.cost))
+                    {
+                        let mut choices = state.choices.clone();
+                        choices.push((item, *direction));
+                        next.insert(
+                            difference + change,
+                            PlanState {
+                                swaps,
+                                cost,
+                                choices,
+                            },
+                        );
+                    }
+                }
+            }
+            states = next;
+        }
+
+        let PlanState { cost, choices, .. } = states.remove(&0)?;
+        let moving = |direction, side: &[Item]| -> Vec<Item> {
+            side.iter()
+                .copied()
+                .filter(|x| choices.contains(&(*x, direction)))
+                .collect()
+        };
+        let swaps = moving(Direction::ToB, &self.side_a)
+            .into_iter()
+            .zip(moving(Direction::ToA, &self.side_b))
+            .map(|(from_a, from_b)| Swap { from_a, from_b })
             .collect();
-        *intersection[0]
+        Some(Reorganization { swaps, cost })
     }
 }
 
-fn get_group_shared(chunk: &[String]) -> u8 {
-    let a_collection = chunk[0].as_bytes();
-    let intersection_a_b: Vec<&u8> = chunk[1]
-        .as_bytes()
-        .iter()
-        .filter(|x| a_collection.contains(x))
-        .collect();
-    let intersection_a_b_c: Vec<&u8> = chunk[2]
-        .as_bytes()
+struct GroupSearch {
+    sets: Vec<ItemSet>,
+    size: usize,
+    used: Vec<bool>,
+    groups: Vec<Vec<usize>>,
+}
+
+impl GroupSearch {
+    // Group up every rucksack that isn't in a group yet, returning whether it could be done
+    fn search(&mut self) -> bool {
+        // Find the rucksack with the fewest possible groups left, giving up straight away if any
+        // rucksack has none
+        let mut fewest: Option<Vec<Vec<usize>>> = None;
+        for member in (0..self.sets.len()).filter(|x| !self.used[*x]) {
+            let limit = fewest.as_ref().map_or(usize::MAX, Vec::len);
+            let options = self.groups_with(member, limit);
+            if options.is_empty() {
+                return false;
+            }
+            if options.len() < limit {
+                fewest = Some(options);
+            }
+        }
+        let Some(options) = fewest else {
+            return true;
+        };
+
+        for group in options {
+            for member in &group {
+                self.used[*member] = true;
+            }
+            self.groups.push(group);
+            if self.search() {
+                return true;
+            }
+            for member in self.groups.pop().unwrap() {
+                self.used[member] = false;
+            }
+        }
+        false
+    }
+
+    // Every group with exactly one badge that `member` could still be part of, stopping once
+    // `limit` have been found
+    fn groups_with(&self, member: usize, limit: usize) -> Vec<Vec<usize>> {
+        let others: Vec<usize> = (0..self.sets.len())
+            .filter(|x| !self.used[*x] && *x != member)
+            .collect();
+        let mut found = Vec::new();
+        let mut group = vec![member];
+        self.fill_group(&others, 0, &mut group, self.sets[member], limit, &mut found);
+        found
+    }
+
+    fn fill_group(
+        &self,
+        others: &[usize],
+        start: usize,
+        group: &mut Vec<usize>,
+        common: ItemSet,
+        limit: usize,
+        found: &mut Vec<Vec<usize>>,
+    ) {
+        // Every item in `common` is shared by the whole group so far, so once it's empty, adding
+        // more rucksacks won't help
+        if common.is_empty() || found.len() >= limit {
+            return;
+        }
+        if group.len() == self.size {
+            if common.len() == 1 {
+                found.push(group.clone());
+            }
+            return;
+        }
+
+        let missing = self.size - group.len();
+        for position in start..others.len() {
+            if others.len() - position < missing {
+                break;
+            }
+            let other = others[position];
+            group.push(other);
+            let shared = common.intersection(self.sets[other]);
+            self.fill_group(others, position + 1, group, shared, limit, found);
+            group.pop();
+        }
+    }
+}
+
+// Find a way to split the rucksacks into groups of `size` (in any order) where every group has
+// exactly one badge, giving the positions of each group's rucksacks. `None` means it can't be done
+pub fn discover_groups(rucksacks: &[Rucksack], size: usize) -> Option<Vec<Vec<usize>>> {
+    assert!(size > 0, "groups need at least one rucksack");
+    if !rucksacks.len().is_multiple_of(size) {
+        return None;
+    }
+    let mut search = GroupSearch {
+        sets: rucksacks.iter().map(Rucksack::get_items).collect(),
+        size,
+        used: vec![false; rucksacks.len()],
+        groups: Vec::new(),
+    };
+    search.search().then_some(search.groups)
+}
+
+#[derive(Default)]
+struct PlanState {
+    swaps: i32,
+    cost: u32,
+    // Which way each item type seen so far is moving
+    choices: Vec<(Item, Direction)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Stay,
+    ToA,
+    ToB,
+}
+
+// Swapping an item from the first compartment with one from the second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub from_a: Item,
+    pub from_b: Item,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorganization {
+    pub swaps: Vec<Swap>,
+    // The total priority of every item that gets moved
+    pub cost: u32,
+}
+
+fn get_group_shared(chunk: &[Rucksack]) -> ItemSet {
+    chunk
         .iter()
-        .filter(|x| intersection_a_b.contains(x))
-        .collect();
-    *intersection_a_b_c[0]
+        .map(Rucksack::get_items)
+        .reduce(ItemSet::intersection)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupBadges {
+    pub group: usize,
+    // Positions of the group's rucksacks in the input
+    pub rucksacks: Range<usize>,
+    // Every item all of the group's rucksacks have in common
+    pub badges: ItemSet,
+}
+
+// Split the rucksacks into groups of `size` in order, and find each group's badges
+pub fn group_badges(rucksacks: &[Rucksack], size: usize) -> Vec<GroupBadges> {
+    assert!(size > 0, "groups need at least one rucksack");
+    rucksacks
+        .chunks(size)
+        .enumerate()
+        .map(|(group, chunk)| GroupBadges {
+            group,
+            rucksacks: group * size..group * size + chunk.len(),
+            badges: get_group_shared(chunk),
+        })
+        .collect()
+}
+
+// Get the groups of `size` that have no badge, or more than one possible badge
+pub fn badge_diagnostics(rucksacks: &[Rucksack], size: usize) -> Vec<GroupBadges> {
+    group_badges(rucksacks, size)
+        .into_iter()
+        .filter(|x| x.badges.len() != 1)
+        .collect()
 }
 
+// A group that part 2 can't take a badge from, as found by `badge_diagnostics`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeError(pub GroupBadges);
+
+impl fmt::Display for BadgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let group = &self.0;
+        write!(
+            f,
+            "group {} (rucksacks {} to {}) has {} possible badges instead of one",
+            group.group,
+            group.rucksacks.start + 1,
+            group.rucksacks.end,
+            group.badges.len()
+        )
+    }
+}
+
+impl Error for BadgeError {}
+
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Vec<Rucksack> {
-    let rucksack_split: Vec<Rucksack> = input
-        .split_whitespace()
-        .map(|x| x.split_at(x.len() / 2))
-        .map(|x| Rucksack {
-            side_a: x.0.to_owned(),
-            side_b: x.1.to_owned(),
+pub fn input_generator(input: &str) -> Result<Vec<Rucksack>, RucksackError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let error = |kind| RucksackError {
+                line: number + 1,
+                kind,
+            };
+            let mut items = line
+                .trim()
+                .chars()
+                .map(Item::try_from)
+                .collect::<Result<Vec<Item>, InvalidItem>>()
+                .map_err(|x| error(RucksackErrorKind::InvalidItem(x)))?;
+            if items.len() % 2 != 0 {
+                return Err(error(RucksackErrorKind::OddLength(items.len())));
+            }
+            let side_b = items.split_off(items.len() / 2);
+            Ok(Rucksack {
+                side_a: items,
+                side_b,
+            })
         })
-        .collect();
-    rucksack_split
+        .collect()
 }
 
 #[aoc(day3, part1)]
 pub fn solver_part1(input: &[Rucksack]) -> u32 {
-    input
-        .iter()
-        .map(|x| get_priority(x.get_shared()))
-        .map(u32::from)
-        .sum()
+    input.iter().map(|x| x.get_shared().priority_sum()).sum()
 }
 
 #[aoc(day3, part2)]
-pub fn solver_part2(input: &[Rucksack]) -> u32 {
-    input
+pub fn solver_part2(input: &[Rucksack]) -> Result<u32, BadgeError> {
+    if let Some(problem) = badge_diagnostics(input, 3).into_iter().next() {
+        return Err(BadgeError(problem));
+    }
+    Ok(group_badges(input, 3)
         .iter()
-        .map(|x| x.side_a.clone() + &x.side_b)
-        .collect::<Vec<String>>()
-        .chunks(3)
-        .map(|x| get_priority(get_group_shared(x)))
-        .map(u32::from)
-        .sum()
+        .map(|x| x.badges.priority_sum())
+        .sum())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn items(line: &str) -> Vec<Item> {
+        line.chars().map(|x| Item::try_from(x).unwrap()).collect()
+    }
+
+    #[test]
+    fn item_set_test() {
+        let set = ItemSet::from_items(&items("vJrwpWtwJgWr"));
+        assert!(set.contains(Item(b'J')));
+        assert_eq!(set.len(), 8);
+
+        let shared = set.intersection(ItemSet::from_items(&items("hcsFMMfFFhFp")));
+        assert_eq!(shared.priorities().collect::<Vec<u8>>(), vec![16]);
+        assert_eq!(shared.priority_sum(), 16);
+        assert!(ItemSet::default().is_empty());
+        assert_eq!(ItemSet::default().priorities().count(), 0);
+    }
+
+    #[test]
+    fn group_badges_test() {
+        let input = input_generator(
+            "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+abcd",
+        )
+        .unwrap();
+        let groups = group_badges(&input, 3);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups[0].badges.items().collect::<Vec<Item>>(),
+            vec![Item(b'r')]
+        );
+        assert_eq!(groups[2].rucksacks, 6..7);
+        assert_eq!(solver_part2(&input[..6]), Ok(70));
+        // The trailing "abcd" group has four possible badges
+        assert_eq!(solver_part2(&input), Err(BadgeError(groups[2].clone())));
+
+        let problems = badge_diagnostics(&input, 3);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].group, 2);
+        assert_eq!(problems[0].badges.len(), 4);
+
+        let problems = badge_diagnostics(&input, 2);
+        assert_eq!(
+            problems.iter().map(|x| x.group).collect::<Vec<usize>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(problems[0].badges.len(), 5);
+
+        let disjoint = input_generator("ab\ncd").unwrap();
+        assert!(badge_diagnostics(&disjoint, 2)[0].badges.is_empty());
+    }
+
+    #[test]
+    fn discover_groups_test() {
+        let input = input_generator(
+            "vJrwpWtwJgWrhcsFMMfFFhFp
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+ttgJtRGJQctTZtZT
+PmmdzqPrVvPwwTWBwg
+CrZsJsPPZsGzwwsLwLmpwMDw",
+        )
+        .unwrap();
+        let groups = discover_groups(&input, 3).unwrap();
+        assert_eq!(groups.len(), 2);
+        let mut seen: Vec<usize> = groups.iter().flatten().copied().collect();
+        seen.sort_unstable();
+        assert_eq!(seen, (0..6).collect::<Vec<usize>>());
+        for group in &groups {
+            let members: Vec<Rucksack> = group.iter().map(|x| input[*x].clone()).collect();
+            assert_eq!(get_group_shared(&members).len(), 1);
+        }
+
+        assert_eq!(discover_groups(&input[..5], 3), None);
+        let disjoint = input_generator("ab\ncd\nab\nab").unwrap();
+        assert_eq!(discover_groups(&disjoint, 2), None);
+    }
+
+    #[test]
+    fn reorganization_test() {
+        let input = input_generator("vJrwpWtwJgWrhcsFMMfFFhFp\naaab\nabcd").unwrap();
+        assert_eq!(
+            input[0].plan_reorganization(),
+            Some(Reorganization {
+                swaps: vec![Swap {
+                    from_a: Item(b'p'),
+                    from_b: Item(b'c'),
+                }],
+                cost: 19,
+            })
+        );
+        assert_eq!(input[1].plan_reorganization(), None);
+        assert_eq!(
+            input[2].plan_reorganization(),
+            Some(Reorganization {
+                swaps: vec![],
+                cost: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn priority_test() {
+        for (character, priority) in [('a', 1), ('z', 26), ('A', 27), ('Z', 52)] {
+            let item = Item::try_from(character).unwrap();
+            assert_eq!(item.priority(), priority);
+            assert_eq!(Item::from_priority(priority), Some(item));
+        }
+        assert_eq!(Item::try_from('1'), Err(InvalidItem('1')));
+        assert_eq!(Item::from_priority(0), None);
+
+        assert_eq!(
+            input_generator("abcA\nab1A").unwrap_err(),
+            RucksackError {
+                line: 2,
+                kind: RucksackErrorKind::InvalidItem(InvalidItem('1')),
+            }
+        );
+        assert_eq!(
+            input_generator("abcA\r\nabc").unwrap_err().kind,
+            RucksackErrorKind::OddLength(3)
+        );
+    }
 }
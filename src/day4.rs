@@ -2,7 +2,9 @@
 //
 // Generator: Splits the input on commas (one range for each pair), then splits on dashes (for
 // lower and higher end of ranges). Constructs an ElfPair struct for each line that consists of two
-// ranges.
+// ranges. Both `Range` and `ElfPair` implement `FromStr`, so a missing separator, a bound that
+// isn't a number or a range like `7-3` (which would throw off the overlap checks) gives back an
+// `AssignmentError` with the line it was found on. Blank lines are skipped.
 //
 // Part 1: Check if the second range is entirely contained within the first range, or if the first
 // range is entirely contained within the second range, using four comparisions.
@@ -10,48 +12,631 @@
 // Part 2: Check if the higher end of the first range exceeds the lower end of the second range, or
 // if the higher end of the second range exceeds the lower end of the first range, to determine if
 // there is any overlap. In hindsight, I could have checked if the first range contained either of
-// the endpoints of the second range, but that might lead to more comparisions. 
+// the endpoints of the second range, but that might lead to more comparisions.
 //
-pub struct Range {
-    lower_end: u32,
-    higher_end: u32,
+// Intervals: Both checks are now built on a generic inclusive `Interval<T>`, and `Range` is just an
+// `Interval<u32>`. Anything that only needs comparisons (containment, overlap, intersection,
+// ordering) works for any `T: Ord`. Merging adjacent intervals, taking differences and counting
+// the values in an interval also need to know what comes right before or after a value, so those
+// are only available for `Discrete` types, which covers all of the integer types.
+//
+// Coverage: To look at every assignment at once instead of pair by pair, each range becomes two
+// events, +1 at its lower end and -1 just past its higher end. Sorting the events and sweeping
+// through them keeps a running count of how many elves cover the current section, which splits the
+// sections into segments with a constant depth. Everything else (the merged union, gaps within some
+// bounds, sections covered by more than K elves) is just filtering and merging those segments.
+//
+// Conflicts: Finding every pair of elves (from any line) with overlapping assignments is another
+// sweep. Elves are sorted by their lower end, and a min-heap keyed on the higher end holds the elves
+// whose assignments are still "open". When the next elf comes in, everything that ended before its
+// lower end gets popped, and whatever is left in the heap overlaps it. Each elf is pushed and popped
+// once, and every other element looked at is a reported conflict, so the sweep is O(n log n + k).
+// Sorting the conflicts afterwards so they come out in a predictable order makes the whole thing
+// O(n log n + k log k).
+//
+// Reassignment: To propose a fixed schedule, each connected part of the union (from `Coverage`) is
+// handled on its own. Its elves are sorted and either keep a slice of it, in that order, or get
+// dropped entirely. Keeping an elf costs how far its two endpoints moved. Dropping one costs the
+// length of its old assignment, which is our own choice: just asking for the smallest endpoint
+// change doesn't say what giving an elf nothing should cost, and this makes it as bad as shrinking
+// its assignment down to nothing. The cost of a cut between two kept elves only changes direction at
+// old endpoints, so the only cut positions worth trying are old lower ends and one past old higher
+// ends. A DP over (elves placed so far, cut position reached) then finds the cheapest schedule that
+// covers the whole part with no overlaps. A kept elf's cost splits into a part for where it starts
+// and a part for where it ends, so keeping a running minimum of the start part while going through
+// the cuts handles each elf in O(C) for C cuts, and only the chosen starts get kept for walking back.
+//
+// Diagrams: `Diagram` draws pairs the way the puzzle does, one row per elf with the last digit of
+// each assigned section and dots everywhere else, followed by a row marking the overlap with `^`.
+// It only draws the sections in its window, and if that's wider than the allowed number of columns,
+// each column stands for several sections and shows `#` when they're all assigned, `+` when some
+// are, and `.` when none are.
+//
+use std::{
+    cmp::Reverse, collections::BinaryHeap, error::Error, fmt, num::ParseIntError, str::FromStr,
+};
+
+// An inclusive interval, which always holds at least one value. Intervals are ordered by their
+// lower end first, then their higher end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T> {
+    lower_end: T,
+    higher_end: T,
+}
+
+pub type Range = Interval<u32>;
+
+impl<T: Ord + Copy> Interval<T> {
+    // Gives `None` if the ends are the wrong way around
+    pub fn new(lower_end: T, higher_end: T) -> Option<Self> {
+        (lower_end <= higher_end).then_some(Interval {
+            lower_end,
+            higher_end,
+        })
+    }
+
+    pub fn lower_end(&self) -> T {
+        self.lower_end
+    }
+
+    pub fn higher_end(&self) -> T {
+        self.higher_end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.lower_end <= value && value <= self.higher_end
+    }
+
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        self.lower_end <= other.lower_end && other.higher_end <= self.higher_end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !(self.higher_end < other.lower_end || other.higher_end < self.lower_end)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Interval::new(
+            self.lower_end.max(other.lower_end),
+            self.higher_end.min(other.higher_end),
+        )
+    }
+
+    // The smallest interval containing both intervals, including anything between them
+    pub fn hull(&self, other: &Self) -> Self {
+        Interval {
+            lower_end: self.lower_end.min(other.lower_end),
+            higher_end: self.higher_end.max(other.higher_end),
+        }
+    }
+}
+
+pub trait Discrete: Ord + Copy {
+    fn predecessor(self) -> Option<Self>;
+    fn successor(self) -> Option<Self>;
+    // The number of values from `self` up to and including `higher`. This is a u128 because the
+    // full range of a u64 holds one value too many for a u64
+    fn count_to(self, higher: Self) -> u128;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(
+            impl Discrete for $t {
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn count_to(self, higher: Self) -> u128 {
+                    (higher as i128 - self as i128 + 1) as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.lower_end, self.higher_end)
+    }
+}
+
+impl<T: Discrete> Interval<T> {
+    // Whether the intervals don't overlap, but there's nothing between them either
+    pub fn is_adjacent(&self, other: &Self) -> bool {
+        self.higher_end.successor() == Some(other.lower_end)
+            || other.higher_end.successor() == Some(self.lower_end)
+    }
+
+    // Merge two intervals into one, if they overlap or sit right next to each other
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        (self.overlaps(other) || self.is_adjacent(other)).then(|| self.hull(other))
+    }
+
+    // Everything in this interval that isn't in `other`, which can be split into a part below
+    // `other` and a part above it
+    pub fn difference(&self, other: &Self) -> (Option<Self>, Option<Self>) {
+        let below = other
+            .lower_end
+            .predecessor()
+            .and_then(|x| Interval::new(self.lower_end, x.min(self.higher_end)));
+        let above = other
+            .higher_end
+            .successor()
+            .and_then(|x| Interval::new(x.max(self.lower_end), self.higher_end));
+        (below, above)
+    }
+
+    // The number of values in the interval
+    pub fn length(&self) -> u128 {
+        self.lower_end.count_to(self.higher_end)
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ElfPair(Range, Range);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRangeError {
+    // There's no '-' between the two ends
+    MissingDash(String),
+    NotANumber {
+        bound: String,
+        source: ParseIntError,
+    },
+    Inverted {
+        lower_end: u32,
+        higher_end: u32,
+    },
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRangeError::MissingDash(range) => {
+                write!(f, "{range:?} is missing the '-' between its ends")
+            }
+            ParseRangeError::NotANumber { bound, source } => {
+                write!(f, "{bound:?} is not a section number: {source}")
+            }
+            ParseRangeError::Inverted {
+                lower_end,
+                higher_end,
+            } => write!(f, "range {lower_end}-{higher_end} starts after it ends"),
+        }
+    }
+}
+
+impl Error for ParseRangeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseRangeError::NotANumber { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lower, higher) = s
+            .split_once('-')
+            .ok_or_else(|| ParseRangeError::MissingDash(s.to_owned()))?;
+        let parse = |bound: &str| {
+            bound
+                .trim()
+                .parse()
+                .map_err(|source| ParseRangeError::NotANumber {
+                    bound: bound.to_owned(),
+                    source,
+                })
+        };
+        let (lower_end, higher_end) = (parse(lower)?, parse(higher)?);
+        Range::new(lower_end, higher_end).ok_or(ParseRangeError::Inverted {
+            lower_end,
+            higher_end,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePairError {
+    // There's no ',' between the two ranges
+    MissingComma,
+    Range(ParseRangeError),
+}
+
+impl fmt::Display for ParsePairError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePairError::MissingComma => write!(f, "missing the ',' between the two ranges"),
+            ParsePairError::Range(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for ParsePairError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParsePairError::Range(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ElfPair {
+    type Err = ParsePairError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.split_once(',').ok_or(ParsePairError::MissingComma)?;
+        let parse = |range: &str| range.parse().map_err(ParsePairError::Range);
+        Ok(ElfPair(parse(first)?, parse(second)?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignmentError {
+    // 1-based line number of the offending line
+    pub line: usize,
+    pub kind: ParsePairError,
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl Error for AssignmentError {}
+
 impl ElfPair {
     fn fully_overlaps(&self) -> bool {
-        let range_1 = &self.0;
-        let range_2 = &self.1;
-
-        range_1.lower_end <= range_2.lower_end && range_1.higher_end >= range_2.higher_end
-            || range_2.lower_end <= range_1.lower_end && range_2.higher_end >= range_1.higher_end
+        self.0.contains_interval(&self.1) || self.1.contains_interval(&self.0)
     }
 
     fn any_overlap(&self) -> bool {
-        let range_1 = &self.0;
-        let range_2 = &self.1;
-        !((range_1.higher_end < range_2.lower_end) || (range_2.higher_end < range_1.lower_end))
+        self.0.overlaps(&self.1)
+    }
+}
+
+// How many elves cover each section, stored as sorted, non-overlapping segments of constant depth.
+// Sections nobody covers don't get a segment
+pub struct Coverage {
+    segments: Vec<(Range, usize)>,
+}
+
+impl Coverage {
+    pub fn new<'a>(ranges: impl IntoIterator<Item = &'a Range>) -> Self {
+        // Positions are u64 so the end event of a range ending at u32::MAX still fits
+        let mut events: Vec<(u64, i64)> = ranges
+            .into_iter()
+            .flat_map(|x| [(x.lower_end as u64, 1), (x.higher_end as u64 + 1, -1)])
+            .collect();
+        events.sort_unstable();
+
+        let mut segments: Vec<(Range, usize)> = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (position, change) in events {
+            if depth > 0 && position > start {
+                let segment = Range {
+                    lower_end: start as u32,
+                    higher_end: (position - 1) as u32,
+                };
+                // Events can change nothing overall (one range ending right where another starts),
+                // so extend the previous segment if the depth is the same
+                match segments.last_mut() {
+                    Some((last, last_depth))
+                        if *last_depth == depth as usize && last.is_adjacent(&segment) =>
+                    {
+                        last.higher_end = segment.higher_end
+                    }
+                    _ => segments.push((segment, depth as usize)),
+                }
+            }
+            depth += change;
+            start = position;
+        }
+
+        Coverage { segments }
+    }
+
+    pub fn from_pairs(pairs: &[ElfPair]) -> Self {
+        Coverage::new(pairs.iter().flat_map(|x| [&x.0, &x.1]))
+    }
+
+    // Segments with their depth, in section order
+    pub fn segments(&self) -> &[(Range, usize)] {
+        &self.segments
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.segments.iter().map(|x| x.1).max().unwrap_or(0)
+    }
+
+    // The normalized union of every assignment
+    pub fn covered(&self) -> Vec<Range> {
+        self.covered_by_more_than(0)
+    }
+
+    // Sections covered by more than `k` elves, with touching segments merged together
+    pub fn covered_by_more_than(&self, k: usize) -> Vec<Range> {
+        let mut merged: Vec<Range> = Vec::new();
+        for (segment, _) in self.segments.iter().filter(|x| x.1 > k) {
+            match merged
+                .last_mut()
+                .and_then(|x| x.union(segment).map(|u| (x, u)))
+            {
+                Some((last, union)) => *last = union,
+                None => merged.push(*segment),
+            }
+        }
+        merged
+    }
+
+    // Sections within `bounds` that nobody covers
+    pub fn uncovered(&self, bounds: Range) -> Vec<Range> {
+        let mut gaps = Vec::new();
+        let mut rest = Some(bounds);
+        for covered in self.covered() {
+            let Some(remaining) = rest else { break };
+            if covered.higher_end < remaining.lower_end {
+                continue;
+            }
+            if covered.lower_end > remaining.higher_end {
+                break;
+            }
+            let (below, above) = remaining.difference(&covered);
+            gaps.extend(below);
+            rest = above;
+        }
+        gaps.extend(rest);
+        gaps
+    }
+}
+
+// Which elf an assignment belongs to: the line (pair) it's on, and whether it's the first (0) or
+// second (1) elf on that line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ElfId {
+    pub pair: usize,
+    pub member: usize,
+}
+
+// Every elf in the input along with its assignment
+fn all_elves(pairs: &[ElfPair]) -> Vec<(ElfId, &Range)> {
+    pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(pair, x)| {
+            [
+                (ElfId { pair, member: 0 }, &x.0),
+                (ElfId { pair, member: 1 }, &x.1),
+            ]
+        })
+        .collect()
+}
+
+// Every pair of elves whose assignments overlap, including the two elves on the same line. Each
+// conflict has the smaller id first, and the list is sorted, which is the O(k log k) part
+pub fn find_conflicts(pairs: &[ElfPair]) -> Vec<(ElfId, ElfId)> {
+    let mut elves = all_elves(pairs);
+    elves.sort_unstable_by_key(|x| (x.1.lower_end, x.0));
+
+    let mut conflicts = Vec::new();
+    let mut open = BinaryHeap::new();
+    for (id, range) in elves {
+        while open
+            .peek()
+            .is_some_and(|Reverse((higher_end, _))| *higher_end < range.lower_end)
+        {
+            open.pop();
+        }
+        conflicts.extend(
+            open.iter()
+                .map(|Reverse((_, other))| (id.min(*other), id.max(*other))),
+        );
+        open.push(Reverse((range.higher_end, id)));
+    }
+
+    conflicts.sort_unstable();
+    conflicts
+}
+
+// A new schedule, indexed like the input: `assignments[pair][member]`, with `None` for elves that
+// got dropped. `cost` is the total endpoint movement plus the length of every dropped assignment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reassignment {
+    pub assignments: Vec<[Option<Range>; 2]>,
+    pub cost: u64,
+}
+
+impl Reassignment {
+    pub fn get(&self, id: ElfId) -> Option<Range> {
+        self.assignments[id.pair][id.member]
+    }
+}
+
+// Marks an elf that got dropped when walking back through the chosen starts
+const DROPPED: u32 = u32::MAX;
+
+pub fn reassign(pairs: &[ElfPair]) -> Reassignment {
+    let mut elves = all_elves(pairs);
+    elves.sort_unstable_by_key(|x| (*x.1, x.0));
+
+    let mut reassignment = Reassignment {
+        assignments: vec![[None; 2]; pairs.len()],
+        cost: 0,
+    };
+    let mut rest = &elves[..];
+    for component in Coverage::from_pairs(pairs).covered() {
+        let count = rest
+            .iter()
+            .take_while(|x| x.1.lower_end <= component.higher_end)
+            .count();
+        let (inside, after) = rest.split_at(count);
+        reassignment.cost += reassign_component(component, inside, &mut reassignment.assignments);
+        rest = after;
+    }
+    reassignment
+}
+
+fn reassign_component(
+    component: Range,
+    elves: &[(ElfId, &Range)],
+    assignments: &mut [[Option<Range>; 2]],
+) -> u64 {
+    // Cuts are where a new assignment starts, or one past where it ends
+    let mut cuts: Vec<u64> = elves
+        .iter()
+        .flat_map(|x| [x.1.lower_end as u64, x.1.higher_end as u64 + 1])
+        .chain([component.lower_end as u64, component.higher_end as u64 + 1])
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    // cost[q]: the cheapest way to handle the elves so far, covering everything before cuts[q]
+    let mut cost: Vec<Option<u64>> = vec![None; cuts.len()];
+    cost[0] = Some(0);
+    // starts[i][q]: the cut elf i's new assignment starts at, if it ends right before cuts[q]
+    let mut starts: Vec<Vec<u32>> = Vec::with_capacity(elves.len());
+    for (_, range) in elves {
+        let mut next: Vec<Option<u64>> = vec![None; cuts.len()];
+        let mut chosen = vec![DROPPED; cuts.len()];
+        // The cheapest place to start so far, including moving the lower end there
+        let mut best_start: Option<(u64, usize)> = None;
+        for q in 0..cuts.len() {
+            if let Some((start_cost, p)) = best_start {
+                next[q] = Some(start_cost + (cuts[q] - 1).abs_diff(range.higher_end as u64));
+                chosen[q] = p as u32;
+            }
+            let Some(so_far) = cost[q] else { continue };
+
+            let dropped = so_far + range.length() as u64;
+            if next[q].is_none_or(|x| dropped < x) {
+                next[q] = Some(dropped);
+                chosen[q] = DROPPED;
+            }
+            let start_cost = so_far + cuts[q].abs_diff(range.lower_end as u64);
+            if best_start.is_none_or(|x| start_cost < x.0) {
+                best_start = Some((start_cost, q));
+            }
+        }
+        cost = next;
+        starts.push(chosen);
+    }
+
+    // Walk back from having covered the whole component
+    let mut q = cuts.len() - 1;
+    for (i, chosen) in starts.iter().enumerate().rev() {
+        if chosen[q] != DROPPED {
+            let p = chosen[q] as usize;
+            let id = elves[i].0;
+            assignments[id.pair][id.member] = Some(Range {
+                lower_end: cuts[p] as u32,
+                higher_end: (cuts[q] - 1) as u32,
+            });
+            q = p;
+        }
+    }
+    cost[cuts.len() - 1].unwrap()
+}
+
+pub struct Diagram {
+    window: Range,
+    sections_per_column: u64,
+}
+
+impl Diagram {
+    // Draws the sections in `window`, using at most `max_columns` columns
+    pub fn new(window: Range, max_columns: usize) -> Self {
+        Diagram {
+            window,
+            sections_per_column: window.length().div_ceil(max_columns.max(1) as u128) as u64,
+        }
+    }
+
+    // A diagram whose window covers every assignment, or `None` if there aren't any
+    pub fn fitting(pairs: &[ElfPair], max_columns: usize) -> Option<Self> {
+        let window = pairs
+            .iter()
+            .map(|x| x.0.hull(&x.1))
+            .reduce(|a, b| a.hull(&b))?;
+        Some(Diagram::new(window, max_columns))
+    }
+
+    fn columns(&self) -> impl Iterator<Item = Range> + '_ {
+        let start = self.window.lower_end as u64;
+        let end = self.window.higher_end as u64;
+        (start..=end)
+            .step_by(self.sections_per_column as usize)
+            .map(move |x| Range {
+                lower_end: x as u32,
+                higher_end: (x + self.sections_per_column - 1).min(end) as u32,
+            })
+    }
+
+    fn row(&self, range: &Range) -> String {
+        self.columns()
+            .map(|column| match column.intersection(range) {
+                None => '.',
+                Some(_) if self.sections_per_column == 1 => {
+                    char::from_digit(column.lower_end % 10, 10).unwrap()
+                }
+                Some(shared) if shared == column => '#',
+                Some(_) => '+',
+            })
+            .collect()
+    }
+
+    pub fn render_pair(&self, pair: &ElfPair) -> String {
+        let mut lines = vec![
+            format!("{}  {}", self.row(&pair.0), pair.0),
+            format!("{}  {}", self.row(&pair.1), pair.1),
+        ];
+        if let Some(overlap) = pair.0.intersection(&pair.1) {
+            let marks: String = self
+                .columns()
+                .map(|x| if x.overlaps(&overlap) { '^' } else { ' ' })
+                .collect();
+            lines.push(format!("{marks}  overlap {overlap}"));
+        }
+        lines.join("\n")
+    }
+
+    // Every pair, separated by blank lines like in the puzzle. Scaled diagrams start with a line
+    // saying how many sections each column is
+    pub fn render(&self, pairs: &[ElfPair]) -> String {
+        let mut blocks: Vec<String> = pairs.iter().map(|x| self.render_pair(x)).collect();
+        if self.sections_per_column > 1 {
+            blocks.insert(
+                0,
+                format!(
+                    "sections {}, {} per column",
+                    self.window, self.sections_per_column
+                ),
+            );
+        }
+        blocks.join("\n\n")
     }
 }
 
 #[aoc_generator(day4)]
-pub fn input_generator(input: &str) -> Vec<ElfPair> {
+pub fn input_generator(input: &str) -> Result<Vec<ElfPair>, AssignmentError> {
     input
         .lines()
-        .map(|x| x.split_once(',').unwrap())
-        .map(|x| (x.0.split_once('-').unwrap(), x.1.split_once('-').unwrap()))
-        .map(|x| {
-            ElfPair(
-                Range {
-                    lower_end: x.0 .0.parse().unwrap(),
-                    higher_end: x.0 .1.parse().unwrap(),
-                },
-                Range {
-                    lower_end: x.1 .0.parse().unwrap(),
-                    higher_end: x.1 .1.parse().unwrap(),
-                },
-            )
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            line.trim().parse().map_err(|kind| AssignmentError {
+                line: number + 1,
+                kind,
+            })
         })
         .collect()
 }
@@ -69,4 +654,189 @@ pub fn solver_part2(input: &[ElfPair]) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    const EXAMPLE: &str = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+
+    #[test]
+    fn example_test() {
+        let input = input_generator(EXAMPLE).unwrap();
+        assert_eq!(solver_part1(&input), 2);
+        assert_eq!(solver_part2(&input), 4);
+    }
+
+    #[test]
+    fn coverage_test() {
+        let interval = |lower, higher| Range::new(lower, higher).unwrap();
+        let coverage = Coverage::from_pairs(&input_generator(EXAMPLE).unwrap());
+
+        assert_eq!(coverage.max_depth(), 8);
+        assert_eq!(coverage.covered(), vec![interval(2, 9)]);
+        assert_eq!(coverage.covered_by_more_than(5), vec![interval(4, 7)]);
+        assert_eq!(
+            coverage.uncovered(interval(1, 10)),
+            vec![interval(1, 1), interval(10, 10)]
+        );
+        assert_eq!(coverage.uncovered(interval(3, 5)), vec![]);
+        assert_eq!(coverage.segments()[2], (interval(4, 5), 7));
+    }
+
+    #[test]
+    fn conflicts_test() {
+        let input = input_generator(EXAMPLE).unwrap();
+        let conflicts = find_conflicts(&input);
+
+        let elves = all_elves(&input);
+        let mut expected = Vec::new();
+        for (i, a) in elves.iter().enumerate() {
+            for b in &elves[i + 1..] {
+                if a.1.overlaps(b.1) {
+                    expected.push((a.0, b.0));
+                }
+            }
+        }
+
+        assert_eq!(conflicts, expected);
+        assert!(!conflicts.contains(&(ElfId { pair: 0, member: 0 }, ElfId { pair: 0, member: 1 })));
+    }
+
+    #[test]
+    fn reassign_test() {
+        let dropped = reassign(&input_generator("1-5,2-3").unwrap());
+        assert_eq!(dropped.cost, 2);
+        assert_eq!(dropped.assignments, vec![[Range::new(1, 5), None]]);
+
+        let input = input_generator(EXAMPLE).unwrap();
+        let reassignment = reassign(&input);
+        let mut cost = 0;
+        let mut new_ranges = Vec::new();
+        for (id, old) in all_elves(&input) {
+            match reassignment.get(id) {
+                Some(new) => {
+                    cost += new.lower_end.abs_diff(old.lower_end) as u64
+                        + new.higher_end.abs_diff(old.higher_end) as u64;
+                    new_ranges.push(new);
+                }
+                None => cost += old.length() as u64,
+            }
+        }
+        let coverage = Coverage::new(&new_ranges);
+
+        assert_eq!(reassignment.cost, cost);
+        assert_eq!(coverage.max_depth(), 1);
+        assert_eq!(coverage.covered(), Coverage::from_pairs(&input).covered());
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!("3-7".parse(), Ok(Range::new(3, 7).unwrap()));
+        assert_eq!(
+            "7-3".parse::<Range>(),
+            Err(ParseRangeError::Inverted {
+                lower_end: 7,
+                higher_end: 3
+            })
+        );
+        assert_eq!(
+            "37".parse::<Range>(),
+            Err(ParseRangeError::MissingDash("37".to_owned()))
+        );
+        assert!(matches!(
+            "3-x".parse::<Range>(),
+            Err(ParseRangeError::NotANumber { bound, .. }) if bound == "x"
+        ));
+        assert_eq!(
+            "2-4 6-8".parse::<ElfPair>(),
+            Err(ParsePairError::MissingComma)
+        );
+
+        assert_eq!(input_generator("2-4,6-8\n\n").unwrap().len(), 1);
+        let error = "2-4,x-8".parse::<ElfPair>().unwrap_err();
+        assert!(error.source().unwrap().is::<ParseRangeError>());
+
+        assert_eq!(
+            input_generator("2-4,6-8\n\n2-3,5-4"),
+            Err(AssignmentError {
+                line: 3,
+                kind: ParsePairError::Range(ParseRangeError::Inverted {
+                    lower_end: 5,
+                    higher_end: 4
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn diagram_test() {
+        let input = input_generator(EXAMPLE).unwrap();
+        let diagram = Diagram::new(Range::new(1, 9).unwrap(), 80);
+        assert_eq!(
+            diagram.render(&input[..3]),
+            ".234.....  2-4
+.....678.  6-8
+
+.23......  2-3
+...45....  4-5
+
+....567..  5-7
+......789  7-9
+      ^    overlap 7-7"
+        );
+
+        let pair: ElfPair = "1-20,2-4".parse().unwrap();
+        let scaled = Diagram::new(Range::new(1, 99).unwrap(), 10);
+        assert_eq!(
+            scaled.render(&[pair]),
+            "sections 1-99, 10 per column
+
+##........  1-20
++.........  2-4
+^           overlap 2-4"
+        );
+        assert_eq!(
+            Diagram::fitting(&input, 80).unwrap().row(&input[0].0),
+            "234....."
+        );
+    }
+
+    #[test]
+    fn interval_test() {
+        let interval = |lower, higher| Interval::new(lower, higher).unwrap();
+        let a = interval(2, 6);
+        let b = interval(4, 8);
+
+        assert_eq!(Interval::new(7, 3), None);
+        assert!(a.contains(6) && !a.contains(7));
+        assert_eq!(a.intersection(&b), Some(interval(4, 6)));
+        assert_eq!(a.intersection(&interval(7, 9)), None);
+        assert_eq!(a.union(&b), Some(interval(2, 8)));
+        assert_eq!(a.union(&interval(7, 9)), Some(interval(2, 9)));
+        assert_eq!(a.union(&interval(8, 9)), None);
+        assert_eq!(a.difference(&b), (Some(interval(2, 3)), None));
+        assert_eq!(
+            a.difference(&interval(3, 4)),
+            (Some(interval(2, 2)), Some(interval(5, 6)))
+        );
+        assert_eq!(a.difference(&interval(0, 9)), (None, None));
+        assert_eq!(a.difference(&interval(8, 9)), (Some(a), None));
+        assert_eq!(
+            interval(8, 9).difference(&interval(1, 2)),
+            (None, Some(interval(8, 9)))
+        );
+        assert_eq!(a.length(), 5);
+        assert!(a < b && interval(2, 5) < a);
+        assert_eq!(Interval::new(-3i64, 3).unwrap().length(), 7);
+        assert_eq!(Interval::new(0u64, u64::MAX).unwrap().length(), 1 << 64);
+        assert_eq!(Interval::new(i64::MIN, i64::MAX).unwrap().length(), 1 << 64);
+        assert_eq!(
+            Interval::new(0u8, 255)
+                .unwrap()
+                .difference(&Interval::new(0, 0).unwrap()),
+            (None, Interval::new(1, 255))
+        );
+    }
 }
@@ -108,8 +108,3 @@ pub fn solver_part2(input: &Input) -> String {
         .collect();
     result
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-}
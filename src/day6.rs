@@ -7,22 +7,202 @@
 // three checks on the input to check if the characters are all unique, stopping checks once we
 // find a window with all unqiue characters. We return the amount of non-matching windows, plus
 // four (since the puzzle wants us to look at the number of characters that need to be processed
-// before the marker is found). 
+// before the marker is found).
 //
 // Part 2: This is solved in the exact same way, except I've generalized part 1 to accept window
 // sizes of any length, performing at most `range-1` comparisions on each window.
 //
+// Bitmask: An alternative to the comparisons above for large inputs. Every lowercase letter gets
+// its own bit in a u32, and we XOR a letter's bit in when it enters the window and out again when
+// it leaves. A bit ends up set only when its letter shows up an odd number of times, so the window
+// is all unique exactly when the number of set bits equals the window size. This does two XORs and
+// a popcount per window, no matter how big the window is. Since there are only 26 letters to give
+// bits to, anything else (like the newline at the end of the input) doesn't get a bit. We just keep
+// count of how many of those are in the window, and check the few windows that have any the slow
+// way, so one odd byte doesn't cost us the fast path for the rest of the stream.
+//
+// Symbols: The marker search doesn't actually care what it's looking at, only that it can compare
+// two things for equality, so it works on any slice. To handle text that isn't plain ASCII, we can
+// search over `char`s (or UTF-16 code units) instead of bytes, so a multi-byte character counts as
+// a single symbol. Each symbol knows how many bytes it takes up, so a marker's position can be
+// reported both in symbols and in bytes.
+//
+// Framing: Once we can find a marker, we can split the rest of the stream into frames. Everything
+// before the first marker is noise and is thrown away. Each marker starts a new frame, whose
+// payload runs up to the start of the next marker (or the end of the stream). Searching for the
+// next marker starts right after the previous one, so markers never overlap.
+//
+// Check whether any symbol in the window appears more than once
+fn has_repeat<T: PartialEq>(window: &[T]) -> bool {
+    (0..window.len()).any(|i| window[i + 1..].contains(&window[i]))
+}
+
+// Get the index of the first window of `size` symbols that are all different. There's no such
+// thing as an empty marker, so a size of 0 never finds one
+pub fn find_marker<T: PartialEq>(data: &[T], size: usize) -> Option<usize> {
+    if size == 0 {
+        return None;
+    }
+    data.windows(size).position(|x| !has_repeat(x))
+}
+
+pub trait Symbol: PartialEq + Copy {
+    // The number of bytes this symbol takes up in the encoded stream
+    fn byte_len(&self) -> usize;
+}
+
+impl Symbol for u8 {
+    fn byte_len(&self) -> usize {
+        1
+    }
+}
+
+impl Symbol for u16 {
+    fn byte_len(&self) -> usize {
+        2
+    }
+}
+
+impl Symbol for char {
+    fn byte_len(&self) -> usize {
+        self.len_utf8()
+    }
+}
+
+// The position right after a marker, which is what the puzzle asks for
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Marker {
+    pub symbol_offset: usize,
+    pub byte_offset: usize,
+}
+
+// Find the first marker of `size` distinct symbols, reporting where it ends
+pub fn find_symbol_marker<T: Symbol>(symbols: &[T], size: usize) -> Option<Marker> {
+    let symbol_offset = find_marker(symbols, size)? + size;
+    let byte_offset = symbols[..symbol_offset].iter().map(Symbol::byte_len).sum();
+    Some(Marker {
+        symbol_offset,
+        byte_offset,
+    })
+}
+
+// Find the first marker of `size` distinct characters in UTF-8 text
+pub fn find_char_marker(input: &str, size: usize) -> Option<Marker> {
+    find_symbol_marker(&input.chars().collect::<Vec<char>>(), size)
+}
+
+// Same as `find_marker`, but keeps a sliding bitmask of the lowercase letters in the window
+pub fn find_marker_bitmask(data: &[u8], size: usize) -> Option<usize> {
+    if size == 0 || size > data.len() {
+        return None;
+    }
+    let bit = |x: u8| {
+        if x.is_ascii_lowercase() {
+            1u32 << (x - b'a')
+        } else {
+            0
+        }
+    };
+    let other = |x: u8| usize::from(!x.is_ascii_lowercase());
+
+    let mut mask = data[..size].iter().fold(0, |acc, x| acc ^ bit(*x));
+    let mut others: usize = data[..size].iter().map(|x| other(*x)).sum();
+    for start in 0..=data.len() - size {
+        let unique = if others == 0 {
+            mask.count_ones() as usize == size
+        } else {
+            !has_repeat(&data[start..start + size])
+        };
+        if unique {
+            return Some(start);
+        }
+        if let Some(&incoming) = data.get(start + size) {
+            mask ^= bit(data[start]) ^ bit(incoming);
+            others = others + other(incoming) - other(data[start]);
+        }
+    }
+    None
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub offset: usize,
+    pub payload: &'a [u8],
+}
+
+impl Frame<'_> {
+    pub fn len(&self) -> usize {
+        self.payload.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payload.is_empty()
+    }
+}
+
+pub struct Frames<'a> {
+    data: &'a [u8],
+    marker_size: usize,
+    // Where the next frame's payload starts, or `None` once the stream is exhausted
+    position: Option<usize>,
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.position?;
+        let rest = &self.data[offset..];
+        let length = if let Some(marker) = find_marker(rest, self.marker_size) {
+            self.position = Some(offset + marker + self.marker_size);
+            marker
+        } else {
+            self.position = None;
+            rest.len()
+        };
+        Some(Frame {
+            offset,
+            payload: &rest[..length],
+        })
+    }
+}
+
+// Split a datastream into frames, each one following a marker of `marker_size` distinct bytes
+pub fn frames(data: &[u8], marker_size: usize) -> Frames<'_> {
+    Frames {
+        data,
+        marker_size,
+        position: find_marker(data, marker_size).map(|x| x + marker_size),
+    }
+}
+
 #[aoc_generator(day6)]
 pub fn input_generator(input: &str) -> String {
     input.to_owned()
 }
 
+// The part 1 check for a repeat, which only works on windows of exactly four bytes
+pub fn repeat_in_four(x: &[u8]) -> bool {
+    x[1..4].contains(&x[0]) || x[2..4].contains(&x[1]) || x[3] == x[2]
+}
+
+// The part 2 check for a repeat, for windows of at least two bytes
+pub fn repeat_in_window(x: &[u8]) -> bool {
+    let range = x.len();
+    for i in 0..range - 2 {
+        if x[i + 1..range].contains(&x[i]) {
+            return true;
+        }
+    }
+    x[range - 1] == x[range - 2]
+}
+
 #[aoc(day6, part1)]
 pub fn solver_part1(input: &String) -> usize {
     input
         .as_bytes()
         .windows(4)
-        .take_while(|x| x[1..4].contains(&x[0]) || x[2..4].contains(&x[1]) || x[3] == x[2])
+        .take_while(|x| repeat_in_four(x))
         .count()
         + 4
 }
@@ -33,19 +213,126 @@ pub fn solver_part2(input: &String) -> usize {
     input
         .as_bytes()
         .windows(range)
-        .take_while(|x| {
-            for i in 0..range - 2 {
-                if x[i + 1..range].contains(&x[i]) {
-                    return true;
-                }
-            }
-            x[range - 1] == x[range - 2]
-        })
+        .take_while(|x| repeat_in_window(x))
         .count()
         + range
 }
 
+#[aoc(day6, part2, Bitmask)]
+pub fn solver_part2_bitmask(input: &String) -> usize {
+    let range = 14;
+    // With no marker, the other solver ends up counting every window
+    let windows = (input.len() + 1).saturating_sub(range);
+    find_marker_bitmask(input.as_bytes(), range).unwrap_or(windows) + range
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn marker_test() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb".to_owned();
+        assert_eq!(solver_part1(&input), 7);
+        assert_eq!(solver_part2(&input), 19);
+        assert_eq!(find_marker(input.as_bytes(), 4), Some(3));
+
+        assert_eq!(find_marker(input.as_bytes(), 0), None);
+        assert_eq!(find_marker_bitmask(input.as_bytes(), 0), None);
+        assert_eq!(find_char_marker(&input, 0), None);
+        assert_eq!(frames(input.as_bytes(), 0).count(), 0);
+    }
+
+    #[test]
+    fn bitmask_test() {
+        let inputs = [
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+            "aaaaaaaa",
+        ];
+        for input in inputs {
+            for input in [
+                input.to_owned(),
+                format!("{input}\n"),
+                format!("\r\n{input}\r\n"),
+            ] {
+                for size in [1, 4, 14, 26, 30] {
+                    assert_eq!(
+                        find_marker_bitmask(input.as_bytes(), size),
+                        find_marker(input.as_bytes(), size),
+                        "{input:?} with size {size}"
+                    );
+                }
+            }
+        }
+
+        // Bytes that would share a bit if they weren't mapped from 'a'
+        for (input, size) in [(&b"aAbc"[..], 4), (b"jj\nj", 2), (b"jjj", 2)] {
+            assert_eq!(find_marker_bitmask(input, size), find_marker(input, size));
+        }
+        assert_eq!(find_marker_bitmask(b"aAbc", 4), Some(0));
+
+        for input in [
+            "aaaaaaaa",
+            "abcdefghijklm",
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n",
+        ] {
+            let input = input.to_owned();
+            assert_eq!(solver_part2_bitmask(&input), solver_part2(&input));
+        }
+    }
+
+    #[test]
+    fn symbol_marker_test() {
+        let input = "aäaäbcd";
+        let expected = Marker {
+            symbol_offset: 6,
+            byte_offset: 8,
+        };
+        assert_eq!(find_char_marker(input, 4), Some(expected));
+
+        let utf16: Vec<u16> = input.encode_utf16().collect();
+        assert_eq!(
+            find_symbol_marker(&utf16, 4),
+            Some(Marker {
+                symbol_offset: 6,
+                byte_offset: 12,
+            })
+        );
+
+        // Splitting the characters into bytes lands on a different marker
+        assert_eq!(
+            find_symbol_marker(input.as_bytes(), 4),
+            Some(Marker {
+                symbol_offset: 7,
+                byte_offset: 7,
+            })
+        );
+    }
+
+    #[test]
+    fn frames_test() {
+        let stream = b"aaabcdxxyyxxwxyzeeefgh";
+        let frames: Vec<Frame> = frames(stream, 4).collect();
+        assert_eq!(
+            frames,
+            vec![
+                Frame {
+                    offset: 6,
+                    payload: b"xxyyxx",
+                },
+                Frame {
+                    offset: 16,
+                    payload: b"ee",
+                },
+                Frame {
+                    offset: 22,
+                    payload: b"",
+                },
+            ]
+        );
+        assert_eq!(frames[0].len(), 6);
+        assert_eq!(super::frames(b"aaaa", 4).count(), 0);
+    }
 }
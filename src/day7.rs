@@ -20,12 +20,45 @@
 // Part 2: Same thing, except we calculate the total size of the root directory, sort the list of
 // sizes, and find the first directory that is just big enough to free up the required amount of
 // space. Too easy, in comparision to the amount of effort it took for everything else...
+// Sizes are kept as u64, and the free space calculation is checked, so a file system that is
+// bigger than the disk (or has nothing big enough to delete) gives back a `SpaceError` instead of
+// wrapping around. If there's already enough free space, nothing needs deleting, so the answer is 0.
+// Adding up file and directory sizes isn't checked though: u64 is the limit we've picked, and it's
+// way beyond anything a puzzle input gets near.
 //
 use std::{
     cell::RefCell,
+    error::Error,
+    fmt,
     rc::{Rc, Weak},
 };
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum SpaceError {
+    // The files take up more space than the disk has
+    Overfull { used: u64, total: u64 },
+    // There is no directory big enough to free up the space we need
+    NoCandidate { needed: u64 },
+}
+
+impl fmt::Display for SpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpaceError::Overfull { used, total } => {
+                write!(
+                    f,
+                    "{used} bytes are in use, but the disk only holds {total}"
+                )
+            }
+            SpaceError::NoCandidate { needed } => {
+                write!(f, "no directory is big enough to free up {needed} bytes")
+            }
+        }
+    }
+}
+
+impl Error for SpaceError {}
+
 #[derive(Clone, Debug)]
 pub enum FileSystemElement {
     File(File),
@@ -51,8 +84,10 @@ impl FileSystemElement {
 
 #[derive(Debug, Clone)]
 pub struct File {
+    // Only kept around for debug output of the tree
+    #[allow(dead_code)]
     name: String,
-    size: u32,
+    size: u64,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -64,7 +99,7 @@ pub struct Directory {
 
 impl Directory {
     // Add a new file to the directory
-    fn add_file(&mut self, name: &str, size: u32) {
+    fn add_file(&mut self, name: &str, size: u64) {
         let file = File {
             name: name.to_owned(),
             size,
@@ -113,12 +148,12 @@ impl Directory {
     }
 
     // Get the total size of all of the files in this directory
-    fn get_content_file_size(&self) -> u32 {
+    fn get_content_file_size(&self) -> u64 {
         self.get_files().into_iter().map(|x| x.size).sum()
     }
 
     // Get the total size of the directory, including the size of any sub-directories
-    fn get_total_size(&self) -> u32 {
+    fn get_total_size(&self) -> u64 {
         // Get size of all files in current directory
         let mut current_size = self.get_content_file_size();
 
@@ -139,7 +174,7 @@ impl Directory {
     }
 
     // Call get_total_size on every child directory found, recursively
-    fn get_all_total_sizes<'a>(&'a self, overall: &'a mut Vec<u32>) -> &'a mut Vec<u32> {
+    fn get_all_total_sizes<'a>(&'a self, overall: &'a mut Vec<u64>) -> &'a mut Vec<u64> {
         let children = self.get_child_directories();
         if !children.is_empty() {
             for child in &children {
@@ -210,7 +245,7 @@ pub fn get_root_directory(input_lines: &[String]) -> Rc<RefCell<Directory>> {
             ReaderState::ReadCommandOutput => {
                 let (arg1, name) = (command_args.first().unwrap(), command_args.get(1).unwrap());
                 // A new file
-                if let Ok(size) = arg1.parse::<u32>() {
+                if let Ok(size) = arg1.parse::<u64>() {
                     active_directory.borrow_mut().add_file(name, size);
                 }
                 // A new directory
@@ -230,12 +265,12 @@ pub fn input_generator(input: &str) -> Vec<String> {
 }
 
 #[aoc(day7, part1)]
-pub fn solver_part1(input: &[String]) -> u32 {
+pub fn solver_part1(input: &[String]) -> u64 {
     let root = get_root_directory(input);
     let borrowed_root = root.borrow();
     let mut total_sizes_result = Vec::new();
     let total_sizes_vec = borrowed_root.get_all_total_sizes(&mut total_sizes_result);
-    let result: u32 = total_sizes_vec
+    let result: u64 = total_sizes_vec
         .iter()
         .copied()
         .filter(|x| x <= &100_000)
@@ -244,14 +279,23 @@ pub fn solver_part1(input: &[String]) -> u32 {
 }
 
 #[aoc(day7, part2)]
-pub fn solver_part2(input: &[String]) -> u32 {
-    const TOTAL_SPACE: u32 = 70_000_000;
-    const NEEDED_SPACE: u32 = 30_000_000;
+pub fn solver_part2(input: &[String]) -> Result<u64, SpaceError> {
+    const TOTAL_SPACE: u64 = 70_000_000;
+    const NEEDED_SPACE: u64 = 30_000_000;
 
     let root = get_root_directory(input);
     let borrowed_root = root.borrow();
 
-    let current_free_space = TOTAL_SPACE - borrowed_root.get_total_size();
+    let used_space = borrowed_root.get_total_size();
+    let current_free_space = TOTAL_SPACE
+        .checked_sub(used_space)
+        .ok_or(SpaceError::Overfull {
+            used: used_space,
+            total: TOTAL_SPACE,
+        })?;
+    if current_free_space >= NEEDED_SPACE {
+        return Ok(0);
+    }
     let space_to_free = NEEDED_SPACE - current_free_space;
 
     println!("current free: {current_free_space:}, space to free: {space_to_free:}");
@@ -262,7 +306,9 @@ pub fn solver_part2(input: &[String]) -> u32 {
         .iter()
         .copied()
         .find(|x| x >= &space_to_free)
-        .unwrap()
+        .ok_or(SpaceError::NoCandidate {
+            needed: space_to_free,
+        })
 }
 
 #[cfg(test)]
@@ -294,20 +340,32 @@ $ ls
 5626152 d.ext
 7214296 k";
         let lines = input_generator(input);
-        let base_root = get_root_directory(lines.as_slice());
-        let root = base_root.borrow().get_directory_by_name("/");
+        let root = get_root_directory(lines.as_slice());
         let borrowed_root = root.borrow();
         println!("{borrowed_root:#?}");
         println!("get_total_size: {:?}", borrowed_root.get_total_size());
         let mut size_vec = Vec::new();
         let all_total_sizes = borrowed_root.get_all_total_sizes(&mut size_vec);
         println!("computed total sizes: {all_total_sizes:?}");
-        let filtered_and_summed: u32 = all_total_sizes
+        let filtered_and_summed: u64 = all_total_sizes
             .iter()
             .copied()
             .filter(|x| x <= &100_000)
             .sum();
         println!("filtered result: {filtered_and_summed:?}");
         assert_eq!(filtered_and_summed, 95437);
+        assert_eq!(solver_part2(&lines), Ok(24933642));
+
+        let overfull = input_generator("$ cd /\n$ ls\n40000000 a\n40000000 b");
+        assert_eq!(
+            solver_part2(&overfull),
+            Err(SpaceError::Overfull {
+                used: 80_000_000,
+                total: 70_000_000,
+            })
+        );
+
+        let roomy = input_generator("$ cd /\n$ ls\n100 a\ndir b\n$ cd b\n$ ls\n200 c");
+        assert_eq!(solver_part2(&roomy), Ok(0));
     }
 }